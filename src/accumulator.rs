@@ -0,0 +1,120 @@
+//! Anytime collections, for algorithms that find many answers rather than one improving answer.
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Mutex,
+};
+
+use crate::diagnostics::{debug, error};
+
+/// An append-only collection of results that keeps growing until a consumer looks at it, after
+/// which it will never change.
+///
+/// This suits enumeration style anytime algorithms (solutions found so far, regions covered so
+/// far, ...) where every update adds to the result instead of replacing it.
+pub struct AnytimeAccumulator<T: Clone> {
+    items: Mutex<Vec<T>>,
+    value_locked: AtomicBool,
+}
+
+impl<T: Clone> AnytimeAccumulator<T> {
+    /// Creates an empty, unlocked accumulator.
+    pub fn new() -> AnytimeAccumulator<T> {
+        AnytimeAccumulator { items: Mutex::new(Vec::new()), value_locked: AtomicBool::new(false) }
+    }
+
+    /// Returns true iff a consumer somewhere has read this collection (thereby freezing it).
+    pub fn is_final(&self) -> bool {
+        self.value_locked.load(Ordering::Relaxed)
+    }
+
+    /// Returns true if anything has been collected, or if the search has been called off.
+    pub fn is_ready(&self) -> bool {
+        self.is_final() || !self.is_empty()
+    }
+
+    /// Returns how many items have been collected so far.
+    pub fn len(&self) -> usize {
+        self.items.lock().map(|items| items.len()).unwrap_or(0)
+    }
+
+    /// Returns true if nothing has been collected yet.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Commits to and returns everything collected so far.  After calling this, adding to the
+    /// collection is a no-op.
+    pub fn get_result(&self) -> Vec<T> {
+        if let Ok(guard) = self.items.lock() {
+            self.value_locked.store(true, Ordering::Relaxed);
+            guard.clone()
+        } else {
+            error!("Attempted to lock a poisoned mutex!  This anytime result cannot recover.");
+            Vec::new()
+        }
+    }
+
+    /// Adds one item to the collection, if possible.
+    pub fn push(&self, item: T) {
+        self.merge(|items| items.push(item));
+    }
+
+    /// Adds several items to the collection at once, if possible.
+    pub fn extend(&self, new_items: impl IntoIterator<Item = T>) {
+        self.merge(|items| items.extend(new_items));
+    }
+
+    /// Grows the collection in place, if possible, for producers that need to merge new results
+    /// into the old ones (deduplicating, coalescing regions, ...) rather than just append them.
+    /// `merge` should only ever add to the collection.
+    pub fn merge(&self, merge: impl FnOnce(&mut Vec<T>)) {
+        if let Ok(mut guard) = self.items.lock() {
+            if !self.value_locked.load(Ordering::Relaxed) {
+                merge(&mut guard);
+            } else {
+                debug!("Attempted to add to a locked collection.");
+            }
+        } else {
+            error!("Attempted to lock a poisoned mutex!  This anytime result cannot recover.");
+        }
+    }
+}
+
+impl<T: Clone> Default for AnytimeAccumulator<T> {
+    fn default() -> AnytimeAccumulator<T> {
+        AnytimeAccumulator::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accumulators_grow_until_they_are_read() {
+        let accumulator = AnytimeAccumulator::new();
+        assert!(!accumulator.is_ready());
+        accumulator.push(1);
+        accumulator.extend([2, 3]);
+        accumulator.merge(|items| {
+            if !items.contains(&3) {
+                items.push(3);
+            }
+        });
+        assert!(accumulator.is_ready());
+        assert_eq!(accumulator.get_result(), vec![1, 2, 3]);
+        accumulator.push(5);
+        assert!(accumulator.is_final());
+        assert_eq!(accumulator.get_result(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn an_empty_accumulator_is_ready_once_read() {
+        let accumulator = AnytimeAccumulator::<u32>::default();
+        assert!(accumulator.get_result().is_empty());
+        assert!(accumulator.is_ready());
+        accumulator.push(1);
+        assert!(accumulator.is_empty());
+    }
+}
@@ -0,0 +1,19 @@
+//! Anytime algorithms written as a series of small steps, for the runners that interleave them.
+
+use crate::Improver;
+
+/// An anytime algorithm that does its work one step at a time, so that a runner can decide when
+/// (and on which thread) each step happens.  See `AnytimeQueue` and `CooperativeRunner`.
+///
+/// Closures taking an `&Improver<T>` and returning a `bool` are algorithms too.
+pub trait AnytimeAlgorithm<T: Clone> {
+    /// Does one step of work, publishing through `improver` whenever it finds something better.
+    /// Returns false once there is nothing more to do.
+    fn step(&mut self, improver: &Improver<T>) -> bool;
+}
+
+impl<T: Clone, F: FnMut(&Improver<T>) -> bool> AnytimeAlgorithm<T> for F {
+    fn step(&mut self, improver: &Improver<T>) -> bool {
+        self(improver)
+    }
+}
@@ -0,0 +1,21 @@
+//! Who supplied which value, for Anytimes fed by a portfolio of producers.
+
+use std::time::Instant;
+
+use crate::ProducerId;
+
+/// One accepted update, as recorded when `AnytimeBuilder::audit_trail` is on.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AuditEntry {
+    /// The version the update created.
+    pub version: u64,
+    /// The producer that supplied the update.
+    pub producer: ProducerId,
+    /// When the update was accepted, according to the Anytime's clock.
+    pub accepted_at: Instant,
+    /// The update's quality, if a quality function was configured.
+    pub quality: Option<f64>,
+    /// Whether this version came from `Anytime::revert_to_previous` (made by `producer`) rather
+    /// than an update, in which case `quality` is that of the value it restored.
+    pub reverted: bool,
+}
@@ -0,0 +1,257 @@
+//! Configuration for Anytimes that need more than the defaults.
+
+use std::{fmt, sync::Arc, time::Duration};
+
+use crate::{
+    Anytime, Clock, Combiner, Compactor, Improver, Quality, ReadBarrier, ReadPolicy, Reader,
+    SameAs, SizeOf, SystemClock, Utility, Validator,
+};
+
+/// Builds an Anytime with non-default settings.  Get one from `Anytime::builder`.
+pub struct AnytimeBuilder<T> {
+    pub(crate) clock: Arc<dyn Clock>,
+    pub(crate) recent_capacity: usize,
+    pub(crate) recycler: Option<Box<dyn Fn(T) + Send + Sync>>,
+    pub(crate) combine: Option<Box<Combiner<T>>>,
+    pub(crate) confidence_target: Option<f64>,
+    pub(crate) quality: Option<Box<Quality<T>>>,
+    pub(crate) utility: Option<Box<Utility>>,
+    pub(crate) memory_budget: Option<(usize, Box<SizeOf<T>>)>,
+    pub(crate) compactor: Option<Box<Compactor<T>>>,
+    pub(crate) validator: Option<Box<Validator<T>>>,
+    pub(crate) same_as: Option<Box<SameAs<T>>>,
+    pub(crate) strict: bool,
+    pub(crate) read_policy: ReadPolicy,
+    pub(crate) clone_outside_lock: bool,
+    pub(crate) read_barrier: Option<ReadBarrier>,
+    pub(crate) track_last_read: bool,
+    pub(crate) audit_trail: bool,
+}
+
+impl<T: Clone> AnytimeBuilder<T> {
+    pub(crate) fn new() -> AnytimeBuilder<T> {
+        AnytimeBuilder {
+            clock: Arc::new(SystemClock),
+            recent_capacity: 1,
+            recycler: None,
+            combine: None,
+            confidence_target: None,
+            quality: None,
+            utility: None,
+            memory_budget: None,
+            compactor: None,
+            validator: None,
+            same_as: None,
+            strict: false,
+            read_policy: ReadPolicy::Wait,
+            clone_outside_lock: false,
+            read_barrier: None,
+            track_last_read: false,
+            audit_trail: false,
+        }
+    }
+
+    /// Sets the clock used for timestamps and ages (the system clock by default).
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> AnytimeBuilder<T> {
+        self.clock = clock;
+        self
+    }
+
+    /// Sets how many of the most recently accepted values `Anytime::recent` keeps, including the
+    /// current best (1 by default, and never less than 1).
+    pub fn recent_capacity(mut self, capacity: usize) -> AnytimeBuilder<T> {
+        self.recent_capacity = capacity.max(1);
+        self
+    }
+
+    /// Hands every value that falls out of the Anytime (because newer values superseded it) to
+    /// `recycler` instead of dropping it, so that large buffers can be returned to a pool or
+    /// partial results logged.  A superseded value is kept for `Anytime::revert_to_previous` until
+    /// the next update pushes it out, or the Anytime becomes final, and the values still kept when
+    /// the Anytime is dropped are recycled then.  The recycler is called without the Anytime
+    /// locked.  With `clone_outside_lock`, or once a contended Anytime serves reads from a snapshot
+    /// (see `AnytimeStats::snapshot_reads`), a value that a read is still cloning when it falls out
+    /// is dropped by that read instead.  So are values still queued for a `Subscription`.
+    pub fn recycler(mut self, recycler: impl Fn(T) + Send + Sync + 'static) -> AnytimeBuilder<T> {
+        self.recycler = Some(Box::new(recycler));
+        self
+    }
+
+    /// Turns the Anytime into an ensemble: instead of exposing the latest update, it remembers the
+    /// latest candidate from every producer and exposes `combine` applied to all of them (each
+    /// paired with its producer's weight, see `Improver::with_weight`).  Handy for ensemble
+    /// estimators and sensor fusion.
+    pub fn ensemble(
+        mut self,
+        combine: impl Fn(&[(&T, f64)]) -> T + Send + Sync + 'static,
+    ) -> AnytimeBuilder<T> {
+        self.combine = Some(Box::new(combine));
+        self
+    }
+
+    /// Finalizes the Anytime as soon as an update arrives whose confidence interval is no wider
+    /// than `target` (see `Anytime::update_with_confidence`).  This is the natural stopping rule
+    /// for stochastic estimators such as Monte Carlo integrals or MCTS values.
+    pub fn confidence_target(mut self, target: f64) -> AnytimeBuilder<T> {
+        self.confidence_target = Some(target);
+        self
+    }
+
+    /// Sets how good a value is, higher being better.  The Anytime only uses this to measure its
+    /// values, never to turn an update down for being worse, but several other options depend on
+    /// it.
+    ///
+    /// In debug builds an update that is worse than the value it replaces is treated as a bug in
+    /// the producer, and panics with a message naming the producer's `ProducerId`.  Updates from a
+    /// producer that outranks the one before it (see `Improver::with_priority`) are exempt, since
+    /// an exact solver is meant to override a heuristic, however well the heuristic scored.
+    pub fn quality(
+        mut self,
+        quality: impl Fn(&T) -> f64 + Send + Sync + 'static,
+    ) -> AnytimeBuilder<T> {
+        self.quality = Some(Box::new(quality));
+        self
+    }
+
+    /// Stops the search once more computation no longer pays for itself.  `utility` maps a
+    /// quality and the time elapsed since the Anytime was created to how much that result would
+    /// be worth; after each update the Anytime assumes the next improvement will be as large and
+    /// take as long as the last one, and finalizes itself if that would not increase the
+    /// utility.  Requires a quality function (see `quality`).
+    pub fn stop_when_unprofitable(
+        mut self,
+        utility: impl Fn(f64, Duration) -> f64 + Send + Sync + 'static,
+    ) -> AnytimeBuilder<T> {
+        self.utility = Some(Box::new(utility));
+        self
+    }
+
+    /// Caps how much memory the Anytime's values may take up, as estimated by `size_of`.  The
+    /// budget covers every value the Anytime keeps (see `recent_capacity`, plus one more for
+    /// `Anytime::revert_to_previous`), and an update that doesn't fit in what's left of it, after
+    /// counting the space it would free, is rejected.  This keeps jobs whose solutions keep growing
+    /// from exhausting the host.  See also `compact_with`.
+    pub fn memory_budget(
+        mut self,
+        bytes: usize,
+        size_of: impl Fn(&T) -> usize + Send + Sync + 'static,
+    ) -> AnytimeBuilder<T> {
+        self.memory_budget = Some((bytes, Box::new(size_of)));
+        self
+    }
+
+    /// Gives updates that don't fit in the memory budget a second chance: `compact` is asked for a
+    /// smaller equivalent of the update, which is stored in its place (if it fits), or None to
+    /// discard the update.  It's called without the Anytime locked.  In an ensemble (see
+    /// `ensemble`) it's the members that are compacted, and a combined value that still doesn't
+    /// fit is rejected.
+    pub fn compact_with(
+        mut self,
+        compact: impl Fn(&T) -> Option<T> + Send + Sync + 'static,
+    ) -> AnytimeBuilder<T> {
+        self.compactor = Some(Box::new(compact));
+        self
+    }
+
+    /// Checks every update before it is offered, rejecting the ones `validate` finds fault with
+    /// (they are counted in `AnytimeStats::invalid_updates`).  This guards the shared result
+    /// against buggy producers such as plugins or third-party solvers.  The validator is called
+    /// without the Anytime locked.
+    pub fn validator<E: fmt::Display>(
+        mut self,
+        validate: impl Fn(&T) -> Result<(), E> + Send + Sync + 'static,
+    ) -> AnytimeBuilder<T> {
+        self.validator = Some(Box::new(move |value| validate(value).map_err(|e| e.to_string())));
+        self
+    }
+
+    /// Skips updates for which `same_as(current, update)` says they are the same as the current
+    /// value (and that come with the same confidence), so that producers re-publishing their best
+    /// candidate don't bump the version or wake observers, subscriptions and waiters for nothing.
+    pub fn skip_duplicates_by(
+        mut self,
+        same_as: impl Fn(&T, &T) -> bool + Send + Sync + 'static,
+    ) -> AnytimeBuilder<T> {
+        self.same_as = Some(Box::new(same_as));
+        self
+    }
+
+    /// Makes reading the Anytime before it holds a value a bug rather than a way of finalizing it
+    /// with nothing (see `Anytime::get_result`).  Off by default.
+    pub fn strict(mut self, strict: bool) -> AnytimeBuilder<T> {
+        self.strict = strict;
+        self
+    }
+
+    /// Sets what a finalizing read does about updates that are in flight when it arrives
+    /// (`ReadPolicy::Wait` by default).
+    pub fn read_policy(mut self, policy: ReadPolicy) -> AnytimeBuilder<T> {
+        self.read_policy = policy;
+        self
+    }
+
+    /// Makes `get_result` and `try_get_result` leave the Anytime improving until `barrier` is
+    /// passed (see `ReadBarrier`).  Other ways of finalizing it, like deadlines, aren't held up.
+    pub fn read_barrier(mut self, barrier: ReadBarrier) -> AnytimeBuilder<T> {
+        self.read_barrier = Some(barrier);
+        self
+    }
+
+    /// Remembers the value each read returns, for `Anytime::last_read` and
+    /// `Anytime::improvement_over_last_read`.  Off by default, since the remembered value is kept
+    /// alive (and so can't be recycled) until the next read.
+    pub fn track_last_read(mut self, track_last_read: bool) -> AnytimeBuilder<T> {
+        self.track_last_read = track_last_read;
+        self
+    }
+
+    /// Makes reads that don't freeze the Anytime (`get_result_leased`, `recent`, views and
+    /// pipelines) clone the value after letting go of the lock rather than while holding it, so an
+    /// expensive `Clone` doesn't hold up producers and other readers.  Off by default.  Reads that
+    /// freeze the Anytime always clone outside the lock.
+    pub fn clone_outside_lock(mut self, clone_outside_lock: bool) -> AnytimeBuilder<T> {
+        self.clone_outside_lock = clone_outside_lock;
+        self
+    }
+
+    /// Records which producer supplied every accepted update, and when, for `Anytime::audit_trail`.
+    /// Off by default, since the record grows with every update.
+    pub fn audit_trail(mut self, audit_trail: bool) -> AnytimeBuilder<T> {
+        self.audit_trail = audit_trail;
+        self
+    }
+
+    /// Creates the configured, empty, unlocked Anytime.
+    pub fn build(self) -> Anytime<T> {
+        Anytime::from_builder(self)
+    }
+
+    /// Creates the configured Anytime already shared, along with a first improver for it, see
+    /// `Anytime::shared`.
+    pub fn build_shared(self) -> (Arc<Anytime<T>>, Improver<T>)
+    where
+        T: Send + Sync,
+    {
+        let anytime = Arc::new(self.build());
+        let improver = anytime.improver();
+        (anytime, improver)
+    }
+
+    /// Creates the configured Anytime already shared, along with a first improver and reader for
+    /// it, see `Anytime::shared_with_reader`.
+    pub fn build_shared_with_reader(self) -> (Arc<Anytime<T>>, Improver<T>, Reader<T>)
+    where
+        T: Send + Sync,
+    {
+        let (anytime, improver) = self.build_shared();
+        let reader = anytime.reader();
+        (anytime, improver, reader)
+    }
+}
+
+impl<T: Clone + PartialEq + 'static> AnytimeBuilder<T> {
+    /// Skips updates equal to the current value, see `skip_duplicates_by`.
+    pub fn skip_duplicates(self) -> AnytimeBuilder<T> {
+        self.skip_duplicates_by(T::eq)
+    }
+}
@@ -0,0 +1,46 @@
+//! Letting producers find out that their work is no longer wanted.
+
+use std::{fmt, future::poll_fn, sync::Arc, task::Poll};
+
+use crate::{Anytime, Finalizable};
+
+/// Trips once the Anytime it was made from becomes final, for whatever reason.  Cheap to clone and
+/// not tied to the Anytime's value type, so it can be handed to any part of a producer.
+#[derive(Clone)]
+pub struct CancellationSignal {
+    target: Arc<dyn Finalizable>,
+}
+
+impl CancellationSignal {
+    /// Creates a signal that trips when `anytime` becomes final.
+    pub fn new<T: Clone + Send + Sync + 'static>(anytime: &Arc<Anytime<T>>) -> CancellationSignal {
+        CancellationSignal { target: Arc::clone(anytime) as Arc<dyn Finalizable> }
+    }
+
+    /// Returns true once the work is no longer wanted.
+    pub fn is_cancelled(&self) -> bool {
+        self.target.is_final()
+    }
+
+    /// Resolves once the work is no longer wanted.
+    pub async fn cancelled(&self) {
+        poll_fn(|cx| {
+            if self.target.is_final() {
+                return Poll::Ready(());
+            }
+            self.target.register_waker(cx.waker());
+            if self.target.is_final() {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        })
+        .await
+    }
+}
+
+impl fmt::Debug for CancellationSignal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CancellationSignal").field("cancelled", &self.is_cancelled()).finish()
+    }
+}
@@ -0,0 +1,137 @@
+//! Where Anytimes (and the machinery around them) get the current time from.
+
+use std::{
+    mem,
+    sync::{Mutex, PoisonError},
+    task::Waker,
+    time::{Duration, Instant},
+};
+
+/// A source of the current time.
+///
+/// Everything time dependent in this crate asks a `Clock` rather than calling `Instant::now`
+/// directly, so that it can be driven deterministically in tests and simulations.
+pub trait Clock: Send + Sync {
+    /// Returns the current time according to this clock.
+    fn now(&self) -> Instant;
+
+    /// Arranges for `waker` to be woken the next time this clock is moved forward, for clocks
+    /// that don't keep real time.  Returns false (the default) for clocks that do, which real-time
+    /// timers can wait on instead.
+    fn wake_on_advance(&self, waker: &Waker) -> bool {
+        let _ = waker;
+        false
+    }
+}
+
+/// The real, monotonic system clock.  This is what everything uses unless told otherwise.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A clock that only moves when it is told to.
+#[derive(Debug)]
+pub struct MockClock {
+    now: Mutex<Instant>,
+    /// Woken by the next `advance`.
+    wakers: Mutex<Vec<Waker>>,
+}
+
+impl MockClock {
+    /// Creates a mock clock that reads the current system time until it is advanced.
+    pub fn new() -> MockClock {
+        MockClock::starting_at(Instant::now())
+    }
+
+    /// Creates a mock clock that reads `start` until it is advanced.
+    pub fn starting_at(start: Instant) -> MockClock {
+        MockClock { now: Mutex::new(start), wakers: Mutex::new(Vec::new()) }
+    }
+
+    /// Moves the clock forward by `by`.
+    pub fn advance(&self, by: Duration) {
+        *self.now.lock().unwrap_or_else(PoisonError::into_inner) += by;
+        let wakers = mem::take(&mut *self.wakers.lock().unwrap_or_else(PoisonError::into_inner));
+        wakers.into_iter().for_each(Waker::wake);
+    }
+}
+
+impl Default for MockClock {
+    fn default() -> MockClock {
+        MockClock::new()
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        *self.now.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn wake_on_advance(&self, waker: &Waker) -> bool {
+        let mut wakers = self.wakers.lock().unwrap_or_else(PoisonError::into_inner);
+        if !wakers.iter().any(|w| w.will_wake(waker)) {
+            wakers.push(waker.clone());
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        task::Wake,
+    };
+
+    use super::*;
+    use crate::Anytime;
+
+    #[test]
+    fn mock_clocks_only_move_when_advanced() {
+        let start = Instant::now();
+        let clock = MockClock::starting_at(start);
+        assert_eq!(clock.now(), start);
+        clock.advance(Duration::from_secs(3));
+        assert_eq!(clock.now(), start + Duration::from_secs(3));
+    }
+
+    /// Records whether it has been woken.
+    struct Woken(AtomicBool);
+
+    impl Wake for Woken {
+        fn wake(self: Arc<Self>) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn advancing_a_mock_clock_wakes_its_waiters_once() {
+        let clock = MockClock::new();
+        let woken = Arc::new(Woken(AtomicBool::new(false)));
+        assert!(clock.wake_on_advance(&Waker::from(Arc::clone(&woken))));
+        clock.advance(Duration::from_secs(1));
+        assert!(woken.0.swap(false, Ordering::SeqCst));
+        clock.advance(Duration::from_secs(1));
+        assert!(!woken.0.load(Ordering::SeqCst));
+        assert!(!SystemClock.wake_on_advance(&Waker::from(woken)));
+    }
+
+    #[test]
+    fn ages_are_measured_with_the_anytimes_clock() {
+        let clock = Arc::new(MockClock::new());
+        let anytime = Anytime::builder().clock(clock.clone()).build();
+        assert_eq!(anytime.age(), None);
+        anytime.update_result(1);
+        assert_eq!(anytime.age(), Some(Duration::ZERO));
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(anytime.age(), Some(Duration::from_secs(5)));
+    }
+}
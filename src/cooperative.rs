@@ -0,0 +1,148 @@
+//! Interleaving many anytime algorithms on a single thread.
+
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::Arc,
+};
+
+use crate::{pause, scope::finalize_panicked, Anytime, AnytimeAlgorithm, Finalizable, Improver};
+
+/// Runs many `AnytimeAlgorithm`s on the calling thread, round-robin, each feeding its own Anytime.
+///
+/// Every algorithm gets a slice of a configurable number of steps per round.  This is for targets
+/// that can't spawn a thread per algorithm (embedded, WASM) but still want all of them improving
+/// at once.  An algorithm is dropped from the rotation once it says it is done or its Anytime
+/// becomes final; one that panics is dropped too, after its Anytime is finalized with
+/// `FinalizeReason::ProducerPanicked`.
+#[derive(Default)]
+pub struct CooperativeRunner<'a> {
+    tasks: Vec<Box<dyn Task + 'a>>,
+}
+
+/// A registered algorithm with its value type erased.
+trait Task {
+    /// Runs one slice, returning false once the algorithm is out of the rotation.
+    fn slice(&mut self) -> bool;
+}
+
+struct Registration<T: Clone, A> {
+    anytime: Arc<Anytime<T>>,
+    improver: Improver<T>,
+    algorithm: A,
+    steps_per_slice: usize,
+}
+
+impl<T, A> Task for Registration<T, A>
+where
+    T: Clone + Send + Sync,
+    A: AnytimeAlgorithm<T>,
+{
+    fn slice(&mut self) -> bool {
+        let Registration { anytime, improver, algorithm, steps_per_slice } = self;
+        let ran = panic::catch_unwind(AssertUnwindSafe(|| {
+            (0..*steps_per_slice).all(|_| !anytime.is_final() && algorithm.step(improver))
+        }));
+        match ran {
+            Ok(more) => more && !anytime.is_final(),
+            Err(payload) => {
+                finalize_panicked(&**anytime as &dyn Finalizable, payload);
+                false
+            }
+        }
+    }
+}
+
+impl<'a> CooperativeRunner<'a> {
+    /// Creates a runner with nothing to run.
+    pub fn new() -> CooperativeRunner<'a> {
+        CooperativeRunner::default()
+    }
+
+    /// Adds `algorithm` to the rotation, feeding `anytime` through its own `Improver` and running
+    /// `steps_per_slice` steps (at least one) per round.
+    pub fn add<T, A>(&mut self, anytime: &Arc<Anytime<T>>, steps_per_slice: usize, algorithm: A)
+    where
+        T: Clone + Send + Sync + 'a,
+        A: AnytimeAlgorithm<T> + 'a,
+    {
+        self.tasks.push(Box::new(Registration {
+            anytime: Arc::clone(anytime),
+            improver: anytime.improver(),
+            algorithm,
+            steps_per_slice: steps_per_slice.max(1),
+        }));
+    }
+
+    /// Returns how many algorithms are still in the rotation.
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    /// Returns true once every algorithm has left the rotation.
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    /// Gives every algorithm one slice, returning how many are still in the rotation afterwards.
+    /// Does nothing while improvement is paused (see `pause`).
+    pub fn run_round(&mut self) -> usize {
+        if !pause::is_paused() {
+            self.tasks.retain_mut(|task| task.slice());
+        }
+        self.tasks.len()
+    }
+
+    /// Runs rounds until every algorithm has left the rotation, blocking while improvement is
+    /// paused.
+    pub fn run(&mut self) {
+        while !self.is_empty() {
+            pause::wait_while_paused(|| false);
+            self.run_round();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FinalizeReason;
+
+    /// An algorithm that counts up to `limit`, one step at a time.
+    fn counting_to(limit: u32) -> impl FnMut(&Improver<u32>) -> bool {
+        let mut count = 0;
+        move |improver| {
+            count += 1;
+            improver.update_result(count);
+            count < limit
+        }
+    }
+
+    #[test]
+    fn every_algorithm_gets_its_slice_each_round() {
+        let (short, long) = (Arc::new(Anytime::new()), Arc::new(Anytime::new()));
+        let mut runner = CooperativeRunner::new();
+        runner.add(&short, 1, counting_to(2));
+        runner.add(&long, 3, counting_to(10));
+        assert_eq!(runner.run_round(), 2);
+        assert_eq!((short.version(), long.version()), (1, 3));
+        assert_eq!(runner.run_round(), 1);
+        runner.run();
+        assert!(runner.is_empty());
+        assert_eq!((short.get_result(), long.get_result()), (Some(2), Some(10)));
+    }
+
+    #[test]
+    fn final_and_panicking_algorithms_leave_the_rotation() {
+        let (finalized, panicking) = (Arc::new(Anytime::new()), Arc::new(Anytime::new()));
+        let mut runner = CooperativeRunner::new();
+        runner.add(&finalized, 1, counting_to(u32::MAX));
+        runner.add(&panicking, 1, |_: &Improver<u32>| panic!("boom"));
+        assert_eq!(runner.run_round(), 1);
+        assert_eq!(
+            panicking.finalize_reason(),
+            Some(FinalizeReason::ProducerPanicked("boom".to_string()))
+        );
+        assert_eq!(finalized.get_result(), Some(1));
+        assert_eq!(runner.run_round(), 0);
+    }
+}
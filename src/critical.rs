@@ -0,0 +1,121 @@
+//! An Anytime for bare-metal targets, built on `critical-section`.  Only available with the
+//! `critical-section` feature.
+
+use core::cell::RefCell;
+
+use critical_section::{CriticalSection, Mutex};
+
+/// A minimal Anytime that can be shared between interrupt handlers and the main loop: updates can
+/// come from an ISR and reads from the main loop (or the other way around).
+///
+/// It never allocates, and `new` is `const`, so a `CsAnytime` can live in a `static`.  In return
+/// it offers only the core of the `Anytime` interface, without observers, history, producers or
+/// any of the builder's options.  Every call runs inside a critical section, so keep `T` cheap to
+/// clone.
+pub struct CsAnytime<T> {
+    state: Mutex<RefCell<CsState<T>>>,
+}
+
+struct CsState<T> {
+    current_best: Option<T>,
+    locked: bool,
+    version: u32,
+}
+
+impl<T: Clone> CsAnytime<T> {
+    /// Creates an empty, unlocked Anytime.
+    pub const fn new() -> CsAnytime<T> {
+        CsAnytime {
+            state: Mutex::new(RefCell::new(CsState {
+                current_best: None,
+                locked: false,
+                version: 0,
+            })),
+        }
+    }
+
+    /// Returns true iff a consumer somewhere has read this result (thereby freezing it).
+    pub fn is_final(&self) -> bool {
+        critical_section::with(|cs| self.state.borrow_ref(cs).locked)
+    }
+
+    /// Returns true if a preliminary result has been found, or if the search has been called off.
+    pub fn is_ready(&self) -> bool {
+        critical_section::with(|cs| {
+            let state = self.state.borrow_ref(cs);
+            state.locked || state.current_best.is_some()
+        })
+    }
+
+    /// Returns the number of updates this Anytime has accepted so far (wrapping on overflow).
+    pub fn version(&self) -> u32 {
+        critical_section::with(|cs| self.state.borrow_ref(cs).version)
+    }
+
+    /// Commits to and returns the best option currently available.  After calling this updates
+    /// are no-ops.
+    pub fn get_result(&self) -> Option<T> {
+        critical_section::with(|cs| self.get_result_in(cs))
+    }
+
+    /// Like `get_result`, for code that is already inside a critical section.
+    pub fn get_result_in(&self, cs: CriticalSection<'_>) -> Option<T> {
+        let mut state = self.state.borrow_ref_mut(cs);
+        state.locked = true;
+        state.current_best.clone()
+    }
+
+    /// Stores an updated result in this anytime, if possible.  Returns false if the Anytime was
+    /// already final, in which case the update was thrown away.
+    pub fn update_result(&self, better_result: T) -> bool {
+        critical_section::with(|cs| self.update_result_in(cs, better_result))
+    }
+
+    /// Like `update_result`, for code that is already inside a critical section (an RTIC resource
+    /// lock, say).
+    pub fn update_result_in(&self, cs: CriticalSection<'_>, better_result: T) -> bool {
+        let mut state = self.state.borrow_ref_mut(cs);
+        if state.locked {
+            return false;
+        }
+        state.current_best = Some(better_result);
+        state.version = state.version.wrapping_add(1);
+        true
+    }
+}
+
+impl<T: Clone> Default for CsAnytime<T> {
+    fn default() -> CsAnytime<T> {
+        CsAnytime::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static SHARED: CsAnytime<u32> = CsAnytime::new();
+
+    #[test]
+    fn cs_anytimes_freeze_on_the_first_read() {
+        assert!(!SHARED.is_ready());
+        assert!(SHARED.update_result(1));
+        assert!(SHARED.update_result(2));
+        assert_eq!(SHARED.version(), 2);
+        assert_eq!(SHARED.get_result(), Some(2));
+        assert!(!SHARED.update_result(3));
+        assert!(SHARED.is_final());
+        assert_eq!((SHARED.get_result(), SHARED.version()), (Some(2), 2));
+    }
+
+    #[test]
+    fn cs_anytimes_can_be_used_from_inside_a_critical_section() {
+        let anytime = CsAnytime::default();
+        critical_section::with(|cs| {
+            assert!(anytime.update_result_in(cs, 'a'));
+            assert_eq!(anytime.get_result_in(cs), Some('a'));
+            assert!(!anytime.update_result_in(cs, 'b'));
+        });
+        assert!(anytime.is_ready() && anytime.is_final());
+    }
+}
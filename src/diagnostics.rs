@@ -0,0 +1,48 @@
+//! Routes the crate's diagnostics to whichever logging framework is enabled: `log` by default, or
+//! `defmt` for embedded targets.  With neither feature the diagnostics are compiled out.
+//!
+//! Messages are handed to defmt as they are, so that it can defer the formatting to the host.
+//! Their arguments must therefore be both `Display` and `defmt::Format`: stick to `&str` and
+//! numbers.
+
+#[cfg(all(feature = "log", feature = "defmt"))]
+compile_error!(
+    "the `log` and `defmt` features are mutually exclusive, disable the default features to use \
+     `defmt`"
+);
+
+#[cfg(feature = "log")]
+macro_rules! debug {
+    ($($arg:tt)*) => { ::log::debug!($($arg)*) };
+}
+
+#[cfg(feature = "log")]
+macro_rules! error {
+    ($($arg:tt)*) => { ::log::error!($($arg)*) };
+}
+
+#[cfg(all(feature = "defmt", not(feature = "log")))]
+macro_rules! debug {
+    ($($arg:tt)*) => { ::defmt::debug!($($arg)*) };
+}
+
+#[cfg(all(feature = "defmt", not(feature = "log")))]
+macro_rules! error {
+    ($($arg:tt)*) => { ::defmt::error!($($arg)*) };
+}
+
+#[cfg(not(any(feature = "log", feature = "defmt")))]
+macro_rules! debug {
+    ($($arg:tt)*) => {{
+        let _ = format_args!($($arg)*);
+    }};
+}
+
+#[cfg(not(any(feature = "log", feature = "defmt")))]
+macro_rules! error {
+    ($($arg:tt)*) => {{
+        let _ = format_args!($($arg)*);
+    }};
+}
+
+pub(crate) use {debug, error};
@@ -0,0 +1,150 @@
+//! Telling observers and subscribers what changed, not just what the value is now.
+
+use crate::{AnytimeObserver, Subscription};
+
+/// Computes what changed between two values.
+type DiffFn<T, D> = dyn Fn(&T, &T) -> D + Send + Sync;
+
+/// Receives the changes a `DiffObserver` computes.
+type OnChange<T, D> = dyn Fn(Change<&T, D>) + Send + Sync;
+
+/// One accepted improvement, with the value it replaced and what changed between the two.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Change<T, D> {
+    /// The version the improvement created.
+    pub version: u64,
+    /// The value that was replaced, or None for the first one.
+    pub old: Option<T>,
+    /// The improved value.
+    pub new: T,
+    /// What changed from `old` to `new`, as computed by the diff function.  None when there is no
+    /// old value to compare with.
+    pub diff: Option<D>,
+}
+
+/// An observer that diffs every accepted update against the value it replaces and hands the
+/// `Change` to a callback, so that a UI can redraw only what changed in a large result (the
+/// route segments that moved, say).
+///
+/// Like any observer it runs while the Anytime is locked, so both the diff function and the
+/// callback should be quick.
+pub struct DiffObserver<T, D> {
+    diff: Box<DiffFn<T, D>>,
+    on_change: Box<OnChange<T, D>>,
+}
+
+impl<T, D> DiffObserver<T, D> {
+    /// Creates an observer that computes `diff(old, new)` for every update and passes the result
+    /// to `on_change`.
+    pub fn new(
+        diff: impl Fn(&T, &T) -> D + Send + Sync + 'static,
+        on_change: impl Fn(Change<&T, D>) + Send + Sync + 'static,
+    ) -> DiffObserver<T, D> {
+        DiffObserver { diff: Box::new(diff), on_change: Box::new(on_change) }
+    }
+}
+
+impl<T, D> AnytimeObserver<T> for DiffObserver<T, D> {
+    fn on_replace(&self, previous: Option<&T>, value: &T, version: u64) {
+        let diff = previous.map(|old| (self.diff)(old, value));
+        (self.on_change)(Change { version, old: previous, new: value, diff });
+    }
+}
+
+impl<T: Clone + Send + 'static> Subscription<T> {
+    /// Turns this subscription into one that hands out every improvement as a `Change` against
+    /// the value it handed out before, diffed with `diff`.
+    ///
+    /// The diff is against what this subscription last saw rather than against whatever the
+    /// Anytime held before, which is what a consumer that redraws incrementally needs.
+    pub fn diffs<D>(self, diff: impl Fn(&T, &T) -> D + Send + Sync + 'static) -> Diffs<T, D> {
+        Diffs { subscription: self, last: None, diff: Box::new(diff) }
+    }
+}
+
+/// A subscription that hands out `Change`s rather than bare values.  Get one from
+/// `Subscription::diffs`.
+pub struct Diffs<T: Clone + Send + 'static, D> {
+    subscription: Subscription<T>,
+    last: Option<T>,
+    diff: Box<DiffFn<T, D>>,
+}
+
+impl<T: Clone + Send + 'static, D> Diffs<T, D> {
+    /// Returns the next change if one has already arrived, without waiting.
+    pub fn try_next(&mut self) -> Option<Change<T, D>> {
+        let new = self.subscription.try_next()?;
+        Some(self.change(new))
+    }
+
+    /// Returns true once nothing more will ever arrive, see `Subscription::is_exhausted`.
+    pub fn is_exhausted(&self) -> bool {
+        self.subscription.is_exhausted()
+    }
+
+    fn change(&mut self, new: T) -> Change<T, D> {
+        let diff = self.last.as_ref().map(|old| (self.diff)(old, &new));
+        let version = self.subscription.last_seen_version();
+        Change { version, old: self.last.replace(new.clone()), new, diff }
+    }
+}
+
+impl<T: Clone + Send + 'static, D> Iterator for Diffs<T, D> {
+    type Item = Change<T, D>;
+
+    fn next(&mut self) -> Option<Change<T, D>> {
+        let new = self.subscription.next()?;
+        Some(self.change(new))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::Anytime;
+
+    /// Diffs lists by the positions that differ.
+    fn changed_positions() -> impl Fn(&Vec<u32>, &Vec<u32>) -> Vec<usize> + Send + Sync {
+        |old, new| (0..old.len().max(new.len())).filter(|&i| old.get(i) != new.get(i)).collect()
+    }
+
+    #[test]
+    fn diff_observers_hand_out_every_change() {
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&changes);
+        let observer =
+            DiffObserver::new(changed_positions(), move |change: Change<&Vec<u32>, _>| {
+                log.lock().unwrap().push((change.version, change.old.cloned(), change.diff))
+            });
+        let anytime = Anytime::new();
+        anytime.add_observer(Box::new(observer));
+        anytime.update_result(vec![1, 2, 3]);
+        anytime.update_result(vec![1, 5, 3, 4]);
+        assert_eq!(
+            *changes.lock().unwrap(),
+            vec![(1, None, None), (2, Some(vec![1, 2, 3]), Some(vec![1, 3]))]
+        );
+    }
+
+    #[test]
+    fn diffing_subscriptions_compare_with_what_they_last_saw() {
+        let anytime = Arc::new(Anytime::new());
+        anytime.update_result(vec![1, 2]);
+        let mut diffs = anytime.subscribe().diffs(changed_positions());
+        let first = diffs.try_next().unwrap();
+        assert_eq!((first.version, first.old, first.diff), (1, None, None));
+        anytime.update_result(vec![1, 3]);
+        anytime.update_result(vec![4, 3]);
+        assert_eq!(anytime.get_result(), Some(vec![4, 3]));
+        let changes: Vec<_> = diffs.map(|c| (c.version, c.old, c.new, c.diff)).collect();
+        assert_eq!(
+            changes,
+            vec![
+                (2, Some(vec![1, 2]), vec![1, 3], Some(vec![1])),
+                (3, Some(vec![1, 3]), vec![4, 3], Some(vec![0]))
+            ]
+        );
+    }
+}
@@ -0,0 +1,52 @@
+//! The errors this crate reports.
+
+use std::{error::Error, fmt};
+
+/// Returned when trying to improve an Anytime that is already final.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Finalized;
+
+impl fmt::Display for Finalized {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("the anytime result is already final")
+    }
+}
+
+impl Error for Finalized {}
+
+/// Returned when asking for a result that doesn't exist yet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NotReady;
+
+impl fmt::Display for NotReady {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("no anytime result is available yet")
+    }
+}
+
+impl Error for NotReady {}
+
+/// Returned when a producer's update is for a round of an `AnytimeRounds` that has since been
+/// superseded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StaleEpoch;
+
+impl fmt::Display for StaleEpoch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("the update is for an earlier round")
+    }
+}
+
+impl Error for StaleEpoch {}
+
+/// Returned when re-attaching a detached producer to an Anytime other than the one it came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WrongAnytime;
+
+impl fmt::Display for WrongAnytime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("the producer was detached from a different anytime result")
+    }
+}
+
+impl Error for WrongAnytime {}
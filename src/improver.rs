@@ -0,0 +1,217 @@
+//! Handles for the producers feeding an Anytime.
+
+use std::{fmt, sync::Arc};
+
+use crate::{pause, Anytime, WrongAnytime};
+
+/// Identifies one producer handle.  Updates made directly through an `Anytime` rather than an
+/// `Improver` are attributed to `ProducerId::ANONYMOUS`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ProducerId(pub(crate) u64);
+
+impl ProducerId {
+    /// The producer behind updates that didn't come through an `Improver`.
+    pub const ANONYMOUS: ProducerId = ProducerId(0);
+}
+
+impl fmt::Display for ProducerId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "producer #{}", self.0)
+    }
+}
+
+/// Who is submitting an update, as far as the update path is concerned.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Producer {
+    pub(crate) id: ProducerId,
+    pub(crate) priority: u32,
+    pub(crate) weight: f64,
+}
+
+impl Producer {
+    pub(crate) const ANONYMOUS: Producer =
+        Producer { id: ProducerId::ANONYMOUS, priority: 0, weight: 1.0 };
+}
+
+/// The producer side of an Anytime.  Get one from `Anytime::improver`.
+///
+/// Every improver carries a priority (0 unless changed with `with_priority`).  Once any producer
+/// has published, updates from producers with a lower priority are ignored, so a cheap heuristic
+/// can seed a result that an exact solver later overrides without the heuristic ever clobbering
+/// the exact answer.  Clones share the identity, priority and weight of the original.
+pub struct Improver<T: Clone> {
+    anytime: Arc<Anytime<T>>,
+    producer: Producer,
+}
+
+impl<T: Clone> Improver<T> {
+    pub(crate) fn new(anytime: Arc<Anytime<T>>, producer: Producer) -> Improver<T> {
+        Improver { anytime, producer }
+    }
+
+    /// Sets the priority this handle publishes with.
+    pub fn with_priority(mut self, priority: u32) -> Improver<T> {
+        self.producer.priority = priority;
+        self
+    }
+
+    /// Sets the weight this handle's candidates carry when the Anytime combines producers into an
+    /// ensemble (see `AnytimeBuilder::ensemble`).  The default is 1.
+    pub fn with_weight(mut self, weight: f64) -> Improver<T> {
+        self.producer.weight = weight;
+        self
+    }
+
+    /// The weight this handle's candidates carry in an ensemble.
+    pub fn weight(&self) -> f64 {
+        self.producer.weight
+    }
+
+    /// This handle's identity.
+    pub fn id(&self) -> ProducerId {
+        self.producer.id
+    }
+
+    /// The priority this handle publishes with.
+    pub fn priority(&self) -> u32 {
+        self.producer.priority
+    }
+
+    /// Lets go of the Anytime, returning a token from which `Anytime::reattach` can later make an
+    /// improver with this one's identity, priority and weight.  Handy for schedulers that migrate
+    /// or suspend producers, since the token doesn't keep the Anytime alive.
+    pub fn detach(self) -> DetachedImprover {
+        DetachedImprover { anytime: self.anytime.instance, producer: self.producer }
+    }
+
+    /// The quality of the Anytime's current value (see `Anytime::quality`), for producers that
+    /// adapt their search to what has already been found.  Doesn't clone the value or freeze
+    /// anything.
+    pub fn best_score(&self) -> Option<f64> {
+        self.anytime.quality()
+    }
+
+    /// The Anytime's current version (see `Anytime::version`), so a producer can tell whether
+    /// anybody has published since it last looked.
+    pub fn current_version(&self) -> u64 {
+        self.anytime.version()
+    }
+
+    /// Returns false once the Anytime is final, at which point further work is wasted.
+    pub fn should_continue(&self) -> bool {
+        !self.anytime.is_final()
+    }
+
+    /// Returns true while improvement is paused (see `pause`).
+    pub fn is_paused(&self) -> bool {
+        pause::is_paused()
+    }
+
+    /// Blocks while improvement is paused (see `pause`), returning early if the Anytime becomes
+    /// final, then returns `should_continue`.  Call it between units of work.
+    pub fn wait_while_paused(&self) -> bool {
+        pause::wait_while_paused(|| self.anytime.is_final());
+        self.should_continue()
+    }
+
+    /// Stores an updated result, unless the Anytime is final or a higher priority producer has
+    /// already published.
+    pub fn update_result(&self, better_result: T) {
+        self.anytime.submit(better_result, None, self.producer);
+    }
+
+    /// Stores the best of a batch of candidates, see `Anytime::update_many`.
+    pub fn update_many(&self, candidates: impl IntoIterator<Item = T>) {
+        self.anytime.submit_many(candidates, self.producer);
+    }
+
+    /// Rolls back the latest accepted update, see `Anytime::revert_to_previous`.  Like an update,
+    /// this is ignored if a higher priority producer has published.
+    pub fn revert_to_previous(&self) -> bool {
+        self.anytime.revert(self.producer)
+    }
+
+    /// Stores an updated result along with the width of its confidence interval, see
+    /// `Anytime::update_with_confidence`.
+    pub fn update_with_confidence(&self, better_result: T, interval: f64) {
+        self.anytime.submit(better_result, Some(interval), self.producer);
+    }
+
+    /// Stores a result and finalizes the Anytime with it, for when this producer has converged,
+    /// see `Anytime::publish_final`.  Like an update, this is ignored if a higher priority producer
+    /// has published.
+    pub fn publish_final(&self, result: T) {
+        self.anytime.submit_final(result, self.producer);
+    }
+}
+
+impl<T: Clone> Clone for Improver<T> {
+    fn clone(&self) -> Improver<T> {
+        Improver { anytime: Arc::clone(&self.anytime), producer: self.producer }
+    }
+}
+
+/// An improver that has let go of its Anytime, see `Improver::detach`.
+#[derive(Clone, Copy, Debug)]
+pub struct DetachedImprover {
+    anytime: u64,
+    producer: Producer,
+}
+
+impl DetachedImprover {
+    /// The identity the improver had, and will have again once re-attached.
+    pub fn id(&self) -> ProducerId {
+        self.producer.id
+    }
+
+    /// Turns this back into an improver for `anytime`, which must be the Anytime it was detached
+    /// from.
+    pub(crate) fn reattach<T: Clone>(
+        self,
+        anytime: &Arc<Anytime<T>>,
+    ) -> Result<Improver<T>, WrongAnytime> {
+        if anytime.instance == self.anytime {
+            Ok(Improver::new(Arc::clone(anytime), self.producer))
+        } else {
+            Err(WrongAnytime)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reattached_improvers_keep_their_identity_priority_and_weight() {
+        let anytime = Arc::new(Anytime::new());
+        let improver = anytime.improver().with_priority(2).with_weight(0.5);
+        let id = improver.id();
+        let detached = improver.detach();
+        assert_eq!((detached.id(), Arc::strong_count(&anytime)), (id, 1));
+        let improver = anytime.reattach(detached).unwrap();
+        assert_eq!((improver.id(), improver.priority(), improver.weight()), (id, 2, 0.5));
+        improver.update_result(1);
+        assert_eq!(anytime.stats().distinct_producers, 1);
+    }
+
+    #[test]
+    fn improvers_only_reattach_to_the_anytime_they_came_from() {
+        let (anytime, other) = (Arc::new(Anytime::<u32>::new()), Arc::new(Anytime::<u32>::new()));
+        let detached = anytime.improver().detach();
+        assert_eq!(other.reattach(detached).err(), Some(WrongAnytime));
+        assert!(anytime.reattach(detached).is_ok());
+    }
+
+    #[test]
+    fn improvers_can_look_at_the_best_so_far_without_freezing_it() {
+        let anytime = Arc::new(Anytime::builder().quality(|v: &u32| f64::from(*v)).build());
+        let improver = anytime.improver();
+        assert_eq!((improver.best_score(), improver.current_version()), (None, 0));
+        anytime.update_result(4);
+        assert_eq!((improver.best_score(), improver.current_version()), (Some(4.0), 1));
+        assert!(improver.should_continue());
+        improver.update_result(5);
+        assert_eq!(anytime.get_result(), Some(5));
+    }
+}
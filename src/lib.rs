@@ -2,8 +2,13 @@
 //! improve the quality of the result and the consumer(s) don't know in advance when they will need
 //! the results.
 //!
-//! Anytime results are by their nature `Sync`, and so it is possible to share one between many
-//! consumers.  If you do share an Anytime all consumers are guaranteed to get the same result(s).
+//! Anytime results are `Sync` whenever their values are `Send + Sync`, and so it is possible to
+//! share one between many consumers.  If you do share an Anytime all consumers are guaranteed to
+//! get the same result(s).
+//!
+//! Everything but `CsAnytime` needs the standard library, behind the default `std` feature.
+//! Without it the crate is `no_std` and doesn't allocate, for bare-metal targets that enable the
+//! `critical-section` feature instead.
 //!
 //! TODO: add an example usage here!!!
 
@@ -16,58 +21,2756 @@
     unused_qualifications
 )]
 #![forbid(unsafe_code)]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "std")]
+mod accumulator;
+#[cfg(feature = "std")]
+mod algorithm;
+#[cfg(feature = "std")]
+mod audit;
+#[cfg(feature = "std")]
+mod builder;
+#[cfg(feature = "std")]
+mod cancel;
+#[cfg(feature = "std")]
+mod clock;
+#[cfg(feature = "std")]
+mod cooperative;
+#[cfg(feature = "critical-section")]
+mod critical;
+#[cfg(feature = "std")]
+mod diagnostics;
+#[cfg(feature = "std")]
+mod diff;
+#[cfg(feature = "std")]
+mod error;
+#[cfg(feature = "std")]
+mod improver;
+#[cfg(feature = "std")]
+mod link;
+#[cfg(feature = "std")]
+mod map;
+#[cfg(feature = "std")]
+mod metrics;
+#[cfg(feature = "std")]
+mod observer;
+#[cfg(feature = "std")]
+mod pareto;
+#[cfg(feature = "std")]
+mod pause;
+#[cfg(feature = "std")]
+mod pipeline;
+#[cfg(feature = "std")]
+mod queue;
+#[cfg(feature = "std")]
+mod reader;
+#[cfg(feature = "std")]
+mod rounds;
+#[cfg(feature = "std")]
+mod scheduler;
+#[cfg(feature = "std")]
+mod scope;
+#[cfg(feature = "std")]
+mod shutdown;
+#[cfg(feature = "async")]
+mod sink;
+#[cfg(feature = "std")]
+mod stats;
+#[cfg(feature = "std")]
+mod subscription;
+#[cfg(all(test, feature = "std"))]
+mod test_support;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "tokio")]
+mod tokio_runner;
+#[cfg(feature = "std")]
+mod view;
+
+#[cfg(feature = "std")]
+use std::{
+    collections::{BTreeMap, HashSet, VecDeque},
+    future::poll_fn,
+    mem,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        mpsc::{Receiver, TryRecvError},
+        Arc, Condvar, LockResult, Mutex, OnceLock, PoisonError, RwLock, TryLockError,
+    },
+    task::{Context, Poll, Waker},
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "std")]
+use crate::diagnostics::{debug, error};
+
+#[cfg(feature = "std")]
+use crate::{
+    improver::Producer,
+    metrics::{LockMeter, Timed},
+    subscription::Feed,
+};
+
+#[cfg(feature = "std")]
+pub use crate::{
+    accumulator::AnytimeAccumulator,
+    algorithm::AnytimeAlgorithm,
+    audit::AuditEntry,
+    builder::AnytimeBuilder,
+    cancel::CancellationSignal,
+    clock::{Clock, MockClock, SystemClock},
+    cooperative::CooperativeRunner,
+    diff::{Change, DiffObserver, Diffs},
+    error::{Finalized, NotReady, StaleEpoch, WrongAnytime},
+    improver::{DetachedImprover, Improver, ProducerId},
+    link::{link, Propagation},
+    map::{AnytimeMap, FreezeScope},
+    observer::{AnytimeObserver, ObserverToken},
+    pareto::AnytimePareto,
+    pause::{is_paused, pause, resume},
+    pipeline::{Pipeline, Refinement},
+    queue::AnytimeQueue,
+    reader::Reader,
+    rounds::{AnytimeRounds, Epoch},
+    scheduler::DeadlineScheduler,
+    scope::{scope, ProducerGroup, ProducerScope},
+    shutdown::shutdown,
+    stats::AnytimeStats,
+    subscription::Subscription,
+    view::View,
+};
+
+#[cfg(feature = "critical-section")]
+pub use crate::critical::CsAnytime;
+#[cfg(feature = "lock-metrics")]
+pub use crate::metrics::LockMetrics;
+#[cfg(feature = "async")]
+pub use crate::sink::SubscriptionStream;
+#[cfg(feature = "tokio")]
+pub use crate::tokio_runner::spawn_tokio;
+
+/// Why an Anytime stopped accepting updates.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FinalizeReason {
+    /// A consumer read the result.
+    Read,
+    /// The deadline it was registered with passed.
+    Deadline,
+    /// An update's confidence interval shrank to the configured target.
+    ConfidenceReached,
+    /// The configured utility function judged further computation not worth its cost.
+    Unprofitable,
+    /// The scope that owned its producers ended.
+    ScopeExit,
+    /// Every `Reader` was dropped without having read it, so nobody is left who wants it.
+    Abandoned,
+    /// A producer run by one of the crate's runners panicked, with this message.
+    ProducerPanicked(String),
+    /// The `AnytimeRounds` it was a round of moved on to the next round.
+    NewRound,
+    /// An Anytime it is linked to was finalized (see `link`).
+    Propagated,
+    /// A producer published its value as final because it can't be improved on (see
+    /// `Improver::publish_final`).
+    Converged,
+    /// The crate's background machinery was shut down (see `shutdown`).
+    Shutdown,
+}
+
+/// Where an Anytime is in its life, coarser than `FinalizeReason` and as policy code tends to want
+/// it.  See `Anytime::state`.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CompletionState {
+    /// It isn't final yet.
+    Improving,
+    /// It finished on its own terms: a producer published a converged value, the confidence target
+    /// was reached, or further work was judged unprofitable.
+    Converged,
+    /// A consumer's read froze it.
+    FrozenByRead,
+    /// Its work was called off before it finished: its scope ended, its readers went away, it was
+    /// superseded by a new round, an Anytime linked to it was finalized or the crate was shut
+    /// down.
+    Cancelled,
+    /// Its deadline passed.
+    TimedOut,
+    /// A producer panicked.
+    Failed,
+}
+
+#[cfg(feature = "std")]
+impl FinalizeReason {
+    fn completion_state(&self) -> CompletionState {
+        match self {
+            FinalizeReason::Converged
+            | FinalizeReason::ConfidenceReached
+            | FinalizeReason::Unprofitable => CompletionState::Converged,
+            FinalizeReason::Read => CompletionState::FrozenByRead,
+            FinalizeReason::ScopeExit
+            | FinalizeReason::Abandoned
+            | FinalizeReason::NewRound
+            | FinalizeReason::Propagated
+            | FinalizeReason::Shutdown => CompletionState::Cancelled,
+            FinalizeReason::Deadline => CompletionState::TimedOut,
+            FinalizeReason::ProducerPanicked(_) => CompletionState::Failed,
+        }
+    }
+}
+
+/// How many of the latest quality measurements `Anytime::estimated_time_to` extrapolates from.
+#[cfg(feature = "std")]
+const PROGRESS_WINDOW: usize = 8;
 
-use std::sync::{atomic::{AtomicBool, Ordering}, Mutex};
+/// How many times taking an Anytime's lock has to wait before reads that change nothing switch to
+/// the snapshot, see `Anytime::lock_state`.
+#[cfg(feature = "std")]
+const CONTENDED_BEFORE_SNAPSHOT: u64 = 32;
 
-use log::{debug, error};
+/// The source of `Anytime::instance`.
+#[cfg(feature = "std")]
+static NEXT_INSTANCE: AtomicU64 = AtomicU64::new(0);
+
+/// What a read that finalizes an Anytime does about an update that is in flight (already holding
+/// the lock, or waiting for it) when the read arrives.  See `AnytimeBuilder::read_policy`.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReadPolicy {
+    /// The read waits its turn, so it returns the value from after any update that got hold of
+    /// the lock first.  Updates that only get hold of the lock after the read are refused.  This
+    /// is the default.
+    Wait,
+    /// Every update still in flight when the read arrives is refused, so the read returns the
+    /// value as it was before them.
+    Surrender,
+}
+
+/// Holds off freezing an Anytime shared by several consumers until more than one of them wants
+/// the result, so that one eager consumer can't cut short an improvement the others were happy to
+/// wait for.  See `AnytimeBuilder::read_barrier`.
+///
+/// Reads that don't get past the barrier return the current best without freezing anything.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReadBarrier {
+    /// Freeze at the read that makes this many distinct consumers that have read.  Every `Reader`
+    /// (clones included) is a consumer of its own, while all reads made directly through the
+    /// Anytime count as one consumer.
+    Consumers(usize),
+    /// Freeze at the first read, or update, once this long has passed since the first read.
+    Grace(Duration),
+}
+
+/// The consumer behind reads that didn't come through a `Reader`.
+#[cfg(feature = "std")]
+pub(crate) const ANONYMOUS_CONSUMER: u64 = 0;
 
 /// A result that could improve until a consumer looks at it, after which it will never change.
+///
+/// Sharing an Anytime between threads takes `T: Send + Sync`, not just `T: Send`, because stored
+/// values (the final one above all) are shared with readers instead of being cloned under the
+/// lock.  Wrap values that aren't `Sync` (because they hold a `Cell`, say) in an `Arc<Mutex<_>>`.
+#[cfg(feature = "std")]
 pub struct Anytime<T: Clone> {
-    current_best: Mutex<Option<T>>,
+    state: Mutex<State<T>>,
+    changed: Condvar,
     value_locked: AtomicBool,
+    /// The bits of the quality floor set with `set_minimum_quality`, NaN if there isn't one.
+    minimum_quality: AtomicU64,
+    /// How many reads with `ReadPolicy::Surrender` are underway.
+    surrendering_reads: AtomicUsize,
+    /// Set once a value has been accepted, for `is_ready_hint`.
+    has_value: AtomicBool,
+    /// A copy of the value it was finalized with, so that reads of a final Anytime skip the lock.
+    final_value: OnceLock<Option<Arc<T>>>,
+    next_observer_token: AtomicU64,
+    next_producer_id: AtomicU64,
+    pub(crate) next_consumer_id: AtomicU64,
+    /// Tells Anytimes apart for as long as the process runs, unlike their addresses.
+    pub(crate) instance: u64,
+    clock: Arc<dyn Clock>,
+    recycler: Option<Box<dyn Fn(T) + Send + Sync>>,
+    combine: Option<Box<Combiner<T>>>,
+    confidence_target: Option<f64>,
+    quality: Option<Box<Quality<T>>>,
+    utility: Option<Box<Utility>>,
+    memory_budget: Option<(usize, Box<SizeOf<T>>)>,
+    compactor: Option<Box<Compactor<T>>>,
+    validator: Option<Box<Validator<T>>>,
+    same_as: Option<Box<SameAs<T>>>,
+    created_at: Instant,
+    receiver: Mutex<Option<Receiver<T>>>,
+    /// How many readers (and views and subscriptions made from them) are alive, see `Reader`.
+    pub(crate) consumer_holds: AtomicUsize,
+    /// Set once a reader (or a view or subscription made from one) has read the result.
+    pub(crate) consumer_read: AtomicBool,
+    /// How many times taking `state` had to wait for another thread.
+    contended_locks: AtomicU64,
+    /// Set once the Anytime has been contended enough that `version`, `is_ready` and `peek` read
+    /// `snapshot` instead of taking `state`.
+    snapshot_reads: AtomicBool,
+    /// The current version and value, kept up to date (under `state`) once `snapshot_reads` is set.
+    snapshot: RwLock<(u64, Option<Arc<T>>)>,
+    lock_meter: LockMeter,
+    strict: bool,
+    read_policy: ReadPolicy,
+    clone_outside_lock: bool,
+    read_barrier: Option<ReadBarrier>,
+    track_last_read: bool,
+}
+
+/// Merges the latest candidate (and weight) of every producer into one value.
+#[cfg(feature = "std")]
+pub(crate) type Combiner<T> = dyn Fn(&[(&T, f64)]) -> T + Send + Sync;
+
+/// Measures how good a value is, higher being better.
+#[cfg(feature = "std")]
+pub(crate) type Quality<T> = dyn Fn(&T) -> f64 + Send + Sync;
+
+/// Values a result of some quality, obtained after some time.
+#[cfg(feature = "std")]
+pub(crate) type Utility = dyn Fn(f64, Duration) -> f64 + Send + Sync;
+
+/// Checks a candidate before it is offered, explaining what is wrong with it if it's invalid.
+#[cfg(feature = "std")]
+pub(crate) type Validator<T> = dyn Fn(&T) -> Result<(), String> + Send + Sync;
+
+/// Decides whether two values are the same, for skipping duplicate updates.
+#[cfg(feature = "std")]
+pub(crate) type SameAs<T> = dyn Fn(&T, &T) -> bool + Send + Sync;
+
+/// Estimates how many bytes a value takes up.
+#[cfg(feature = "std")]
+pub(crate) type SizeOf<T> = dyn Fn(&T) -> usize + Send + Sync;
+
+/// Makes a smaller copy of a value that doesn't fit in the memory budget, if it can.
+#[cfg(feature = "std")]
+pub(crate) type Compactor<T> = dyn Fn(&T) -> Option<T> + Send + Sync;
+
+/// Crate machinery to run once an Anytime is final, see `Anytime::on_finalized`.
+#[cfg(feature = "std")]
+type FinalizeHook = Box<dyn FnOnce(&FinalizeReason) + Send>;
+
+/// The latest candidate from each producer of an ensemble, with its weight.
+#[cfg(feature = "std")]
+type Members<T> = BTreeMap<ProducerId, (Arc<T>, f64)>;
+
+/// An update that's ready to be swapped in.
+#[cfg(feature = "std")]
+struct Staged<T> {
+    value: Arc<T>,
+    quality: Option<f64>,
+    confidence: Option<f64>,
+    /// The value's estimated size, if there's a memory budget.
+    size: usize,
+    now: Instant,
+    /// Whether the producer says nothing better will come, so storing it finalizes the Anytime.
+    converged: bool,
 }
 
+/// What is remembered about an accepted value besides the value itself.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy)]
+struct Accepted {
+    /// The value's estimated size, if there's a memory budget.
+    size: usize,
+    quality: Option<f64>,
+    confidence: Option<f64>,
+}
+
+/// The locked state of an Anytime.
+#[cfg(feature = "std")]
+type StateGuard<'a, T> = Timed<'a, State<T>>;
+
+/// Everything about an Anytime that has to change together.
+#[cfg(feature = "std")]
+struct State<T> {
+    /// The most recently accepted values, oldest first; the back is the current best.  One more
+    /// than `recent_capacity` are kept, so that the latest update can be reverted.
+    recent: VecDeque<Arc<T>>,
+    /// What was known about each of the values in `recent` when it was accepted.
+    recent_meta: VecDeque<Accepted>,
+    retained_bytes: usize,
+    recent_capacity: usize,
+    updated_at: Option<Instant>,
+    version: u64,
+    observers: Vec<(ObserverToken, Box<dyn AnytimeObserver<T>>)>,
+    /// What to run once the Anytime is final and its lock has been let go.
+    finalize_hooks: Vec<FinalizeHook>,
+    /// The queues of the subscriptions following this Anytime.
+    feeds: Vec<(ObserverToken, Arc<Feed<T>>)>,
+    wakers: Vec<Waker>,
+    stats: AnytimeStats,
+    producers_seen: HashSet<ProducerId>,
+    /// The highest priority any producer has published with so far.
+    top_priority: Option<u32>,
+    /// Until when a leased read is holding the value still.
+    lease_until: Option<Instant>,
+    /// The members each of the `recent` values was combined from, when combining them into an
+    /// ensemble, so that reverting a value reverts its members too.
+    ensembles: VecDeque<Members<T>>,
+    /// The confidence interval width reported along with the current value.
+    confidence: Option<f64>,
+    /// The quality of the current value, if a quality function was configured.
+    quality: Option<f64>,
+    /// Why the Anytime became final, once it has.
+    finalize_reason: Option<FinalizeReason>,
+    /// Every accepted update in order, if the audit trail is on.
+    audit: Option<Vec<AuditEntry>>,
+    /// When the latest few accepted values with a quality were accepted, and their qualities.
+    progress: VecDeque<(Instant, f64)>,
+    /// The consumers that have read so far, for `ReadBarrier::Consumers`.
+    consumers_seen: HashSet<u64>,
+    /// When the grace window that the first read opened closes, for `ReadBarrier::Grace`.
+    grace_until: Option<Instant>,
+    /// The value the latest read returned and its quality, if the Anytime keeps track.
+    last_read: Option<(Arc<T>, Option<f64>)>,
+}
+
+#[cfg(feature = "std")]
 impl<T: Clone> Anytime<T> {
     /// Creates an empty, unlocked Anytime.
     pub fn new() -> Anytime<T> {
+        Anytime::builder().build()
+    }
+
+    /// Creates an Anytime that is already final and holds `value`, for answers that are known up
+    /// front (cached results, trivial cases) but have to be handed out where an Anytime is
+    /// expected.  Reads return `value` straight away, and the Anytime counts as finalized with
+    /// `FinalizeReason::Converged`.
+    pub fn finalized(value: T) -> Anytime<T> {
+        let anytime = Anytime::new();
+        anytime.publish_final(value);
+        anytime
+    }
+
+    /// Creates an empty, unlocked Anytime that is ready to be shared between threads, along with an
+    /// improver for the producer.  Clone the improver for more producer threads, and hand out
+    /// readers with `Anytime::reader`.
+    pub fn shared() -> (Arc<Anytime<T>>, Improver<T>)
+    where
+        T: Send + Sync,
+    {
+        Anytime::builder().build_shared()
+    }
+
+    /// Like `shared`, but also returns a reader for the consumer.
+    ///
+    /// The Anytime is abandoned once every reader is gone without having read it (see `Reader`),
+    /// so keep the reader even if the Anytime itself will be read: dropping it straight away
+    /// finalizes the Anytime.  Use
+    /// `shared` if the consumer doesn't need one.
+    pub fn shared_with_reader() -> (Arc<Anytime<T>>, Improver<T>, Reader<T>)
+    where
+        T: Send + Sync,
+    {
+        Anytime::builder().build_shared_with_reader()
+    }
+
+    /// Starts configuring an Anytime that needs non-default settings.
+    pub fn builder() -> AnytimeBuilder<T> {
+        AnytimeBuilder::new()
+    }
+
+    fn from_builder(builder: AnytimeBuilder<T>) -> Anytime<T> {
         Anytime {
-            current_best: Mutex::new(None),
+            state: Mutex::new(State {
+                recent: VecDeque::with_capacity(builder.recent_capacity + 1),
+                recent_meta: VecDeque::with_capacity(builder.recent_capacity + 1),
+                retained_bytes: 0,
+                recent_capacity: builder.recent_capacity,
+                updated_at: None,
+                version: 0,
+                observers: Vec::new(),
+                finalize_hooks: Vec::new(),
+                feeds: Vec::new(),
+                wakers: Vec::new(),
+                stats: AnytimeStats::default(),
+                producers_seen: HashSet::new(),
+                top_priority: None,
+                lease_until: None,
+                ensembles: VecDeque::new(),
+                confidence: None,
+                quality: None,
+                progress: VecDeque::with_capacity(PROGRESS_WINDOW),
+                consumers_seen: HashSet::new(),
+                grace_until: None,
+                last_read: None,
+                finalize_reason: None,
+                audit: if builder.audit_trail { Some(Vec::new()) } else { None },
+            }),
+            changed: Condvar::new(),
             value_locked: AtomicBool::new(false),
+            has_value: AtomicBool::new(false),
+            surrendering_reads: AtomicUsize::new(0),
+            minimum_quality: AtomicU64::new(f64::NAN.to_bits()),
+            final_value: OnceLock::new(),
+            next_observer_token: AtomicU64::new(0),
+            next_producer_id: AtomicU64::new(1),
+            next_consumer_id: AtomicU64::new(1),
+            instance: NEXT_INSTANCE.fetch_add(1, Ordering::Relaxed),
+            clock: Arc::clone(&builder.clock),
+            recycler: builder.recycler,
+            combine: builder.combine,
+            confidence_target: builder.confidence_target,
+            quality: builder.quality,
+            utility: builder.utility,
+            memory_budget: builder.memory_budget,
+            compactor: builder.compactor,
+            validator: builder.validator,
+            same_as: builder.same_as,
+            created_at: builder.clock.now(),
+            receiver: Mutex::new(None),
+            consumer_holds: AtomicUsize::new(0),
+            consumer_read: AtomicBool::new(false),
+            contended_locks: AtomicU64::new(0),
+            snapshot_reads: AtomicBool::new(false),
+            snapshot: RwLock::new((0, None)),
+            lock_meter: LockMeter::new(),
+            strict: builder.strict,
+            clone_outside_lock: builder.clone_outside_lock,
+            read_barrier: builder.read_barrier,
+            track_last_read: builder.track_last_read,
+            read_policy: builder.read_policy,
         }
     }
 
-    /// Returns true iff a consumer somewhere has read this result (thereby freezing it).
+    /// Returns true once this result is final, whether a consumer froze it by reading it or it was
+    /// finalized for any other reason (see `finalize_reason`).
     pub fn is_final(&self) -> bool {
         self.value_locked.load(Ordering::Relaxed)
     }
 
-    /// Returns true if a preliminary result has been found, or if the search has been called off.
+    /// Returns true if a preliminary result has been found (one that meets the quality floor, if
+    /// one was set with `set_minimum_quality`), or if the search has been called off.
     pub fn is_ready(&self) -> bool {
-        self.is_final() || self.current_best.lock().unwrap().is_some()
+        if self.final_value.get().is_some() {
+            return true;
+        }
+        self.drain_receiver();
+        if let Some((_, value)) = self.snapshot().filter(|_| self.floor().is_none()) {
+            return value.is_some() || self.is_final();
+        }
+        self.is_final() || self.lock_state().map(|s| self.meets_floor(&s)).unwrap_or(false)
+    }
+
+    /// Sets the lowest quality (see `AnytimeBuilder::quality`) a consumer is willing to act on.
+    /// Until a value at or above `floor` has been found `is_ready` reports false,
+    /// `try_get_result` returns `Err(NotReady)` without freezing anything, and `get_result` still
+    /// freezes the Anytime but returns None; `get_result_anyway` returns the value regardless.
+    ///
+    /// Values only have a quality if a quality function was configured, so without one no value
+    /// meets the floor.  Setting the floor to NaN removes it.
+    pub fn set_minimum_quality(&self, floor: f64) {
+        self.minimum_quality.store(floor.to_bits(), Ordering::Relaxed);
+        // A lower floor can make the Anytime ready for whoever is polling it.
+        if let Ok(guard) = self.lock_state() {
+            self.announce_change(guard);
+        }
+    }
+
+    /// A cheap, approximate `is_ready` for hot loops (once per frame, say) where even an
+    /// uncontended lock costs too much: it never blocks and is a single atomic read.  It may lag
+    /// behind the precise answer for a moment, and unlike `is_ready` it doesn't drain an attached
+    /// receiver.  Once it has returned true it keeps doing so.
+    pub fn is_ready_hint(&self) -> bool {
+        self.has_value.load(Ordering::Relaxed) || self.is_final()
+    }
+
+    /// Returns the number of updates this Anytime has accepted so far.
+    pub fn version(&self) -> u64 {
+        if let Some((version, _)) = self.snapshot() {
+            return version;
+        }
+        self.lock_state().map(|s| s.version).unwrap_or(0)
+    }
+
+    /// Returns a snapshot of this Anytime's counters.
+    pub fn stats(&self) -> AnytimeStats {
+        let mut stats = self.lock_state().map(|s| s.stats.clone()).unwrap_or_default();
+        stats.contended_locks = self.contended_locks.load(Ordering::Relaxed);
+        stats.snapshot_reads = self.snapshot_reads.load(Ordering::Relaxed);
+        #[cfg(feature = "lock-metrics")]
+        {
+            stats.lock_metrics = self.lock_meter.snapshot();
+        }
+        stats
+    }
+
+    /// Returns the most recently accepted values, oldest first and ending with the current best.
+    /// How many are kept is set with `AnytimeBuilder::recent_capacity` (just the current best by
+    /// default).  Unlike `get_result` this doesn't freeze anything.
+    pub fn recent(&self) -> Vec<T> {
+        self.drain_receiver();
+        let guard = match self.lock_state() {
+            Ok(guard) => guard,
+            Err(_) => return Vec::new(),
+        };
+        let skipped = guard.recent.len().saturating_sub(guard.recent_capacity);
+        let recent = guard.recent.iter().skip(skipped);
+        if self.clone_outside_lock {
+            let snapshots: Vec<Arc<T>> = recent.cloned().collect();
+            drop(guard);
+            snapshots.iter().map(|v| T::clone(v)).collect()
+        } else {
+            recent.map(|v| T::clone(v)).collect()
+        }
+    }
+
+    /// Returns how long ago the current value was stored (according to this Anytime's clock), or
+    /// None if nothing has been stored yet.
+    pub fn age(&self) -> Option<Duration> {
+        let updated_at = self.lock_state().ok()?.updated_at?;
+        Some(self.clock.now().saturating_duration_since(updated_at))
     }
 
     /// Commits to and returns the best option currently available.  After calling this, calling
     /// update_result is a no-op.
+    ///
+    /// In strict mode (see `AnytimeBuilder::strict`) reading an Anytime that has nothing in it yet
+    /// is treated as a bug: debug builds panic, and release builds return None without freezing.
+    ///
+    /// With a read barrier (see `AnytimeBuilder::read_barrier`) reads only freeze the Anytime once
+    /// the barrier is passed, until then they return the current best and it keeps improving.
+    ///
+    /// If the current value is below the quality floor (see `set_minimum_quality`) this returns
+    /// None, though the Anytime is frozen all the same.
     pub fn get_result(&self) -> Option<T> {
-        if let Ok(guard) = self.current_best.lock() {
-            self.value_locked.store(true, Ordering::Relaxed);
-            guard.clone()
+        self.read(ANONYMOUS_CONSUMER, true)
+    }
+
+    /// Like `get_result`, but returns the value even if it is below the quality floor (see
+    /// `set_minimum_quality`), for emergencies.
+    pub fn get_result_anyway(&self) -> Option<T> {
+        self.read(ANONYMOUS_CONSUMER, false)
+    }
+
+    /// `get_result` on behalf of `consumer`, or `get_result_anyway` unless `floored`.
+    pub(crate) fn read(&self, consumer: u64, floored: bool) -> Option<T> {
+        let floored = floored && self.floor().is_some();
+        if let Some(result) = self.final_value.get() {
+            if floored && !self.lock_state().is_ok_and(|s| self.meets_floor(&s)) {
+                return None;
+            }
+            self.note_final_read();
+            return result.as_deref().cloned();
+        }
+        self.drain_receiver();
+        let _surrender = self.surrender();
+        if let Ok(mut guard) = self.lock_state() {
+            if self.strict && guard.current_best().is_none() && !self.is_final() {
+                // Panicking with the lock held would poison it for good.
+                drop(guard);
+                debug_assert!(false, "Read a strict Anytime before it was ready.");
+                return None;
+            }
+            let below_floor = floored && !self.meets_floor(&guard);
+            if !below_floor {
+                self.note_read(&mut guard);
+            }
+            if !self.passes_barrier(&mut guard, consumer) {
+                return if below_floor { None } else { self.copy_out(guard) };
+            }
+            self.freeze(guard, FinalizeReason::Read);
+            if below_floor {
+                return None;
+            }
+            // Only clone the value once the lock has been released.
+            self.final_value.get()?.as_deref().cloned()
+        } else {
+            error!("Attempted to lock a poisoned mutex!  This anytime result cannot recover.");
+            None
+        }
+    }
+
+    /// Like `get_result`, except that if there's nothing to return it returns `Err(NotReady)`
+    /// rather than freezing the Anytime with nothing in it.
+    pub fn try_get_result(&self) -> Result<T, NotReady> {
+        self.try_read(ANONYMOUS_CONSUMER)
+    }
+
+    /// `try_get_result` on behalf of `consumer`.
+    pub(crate) fn try_read(&self, consumer: u64) -> Result<T, NotReady> {
+        if let Some(result) = self.final_value.get() {
+            if self.floor().is_some() && !self.lock_state().is_ok_and(|s| self.meets_floor(&s)) {
+                return Err(NotReady);
+            }
+            self.note_final_read();
+            return result.as_deref().cloned().ok_or(NotReady);
+        }
+        self.drain_receiver();
+        let _surrender = self.surrender();
+        if let Ok(mut guard) = self.lock_state() {
+            if !self.meets_floor(&guard) {
+                return Err(NotReady);
+            }
+            self.note_read(&mut guard);
+            if !self.passes_barrier(&mut guard, consumer) {
+                return self.copy_out(guard).ok_or(NotReady);
+            }
+            self.freeze(guard, FinalizeReason::Read);
+            self.final_value.get().and_then(|v| v.as_deref().cloned()).ok_or(NotReady)
+        } else {
+            error!("Attempted to lock a poisoned mutex!  This anytime result cannot recover.");
+            Err(NotReady)
+        }
+    }
+
+    /// Like `get_result`, except that if there's nothing to return yet `fallback` (typically a
+    /// cheap greedy baseline) is run right away and the Anytime is finalized with its output.  A
+    /// value that a producer publishes while `fallback` is running still takes precedence over it.
+    ///
+    /// Returns None only if the Anytime was finalized with nothing by somebody else in the
+    /// meantime.
+    pub fn get_result_or_else_compute(&self, fallback: impl FnOnce() -> T) -> Option<T> {
+        self.get_result_or_else_compute_within(Duration::MAX, |_| Some(fallback()))
+    }
+
+    /// Like `get_result_or_else_compute`, but gives `fallback` a time budget (measured with this
+    /// Anytime's clock).  `fallback` is handed a function that returns true once the budget is
+    /// spent; it should check it now and then and return its best effort, or None if it has
+    /// nothing, when time is up.
+    pub fn get_result_or_else_compute_within(
+        &self,
+        budget: Duration,
+        fallback: impl FnOnce(&dyn Fn() -> bool) -> Option<T>,
+    ) -> Option<T> {
+        self.read_or_else_compute(ANONYMOUS_CONSUMER, budget, fallback)
+    }
+
+    /// `get_result_or_else_compute_within` on behalf of `consumer`.
+    pub(crate) fn read_or_else_compute(
+        &self,
+        consumer: u64,
+        budget: Duration,
+        fallback: impl FnOnce(&dyn Fn() -> bool) -> Option<T>,
+    ) -> Option<T> {
+        if !self.is_ready() {
+            let started = self.clock.now();
+            let out_of_time = || self.clock.now().saturating_duration_since(started) >= budget;
+            if let Some(baseline) = fallback(&out_of_time) {
+                self.submit_fallback(baseline);
+            }
+        }
+        self.read(consumer, true)
+    }
+
+    /// Returns the best option currently available and holds it still for `lease`, after which
+    /// the value may improve again.  This gives a consumer consistency while it acts on the value
+    /// without permanently ending the search.
+    ///
+    /// A leased read doesn't make the Anytime final; if it already is final this behaves just like
+    /// `get_result`.  Like `get_result` it returns None while the value is below the quality floor
+    /// (see `set_minimum_quality`), and reading an Anytime that has nothing worth holding yet
+    /// leases nothing, so a better value (a fallback's included) can still get in.
+    pub fn get_result_leased(&self, lease: Duration) -> Option<T> {
+        if self.final_value.get().is_some() {
+            return self.read(ANONYMOUS_CONSUMER, true);
+        }
+        self.drain_receiver();
+        if let Ok(mut guard) = self.lock_state() {
+            if !self.meets_floor(&guard) {
+                return None;
+            }
+            let until = self.clock.now() + lease;
+            guard.lease_until = guard.lease_until.max(Some(until));
+            self.note_read(&mut guard);
+            self.copy_out(guard)
         } else {
             error!("Attempted to lock a poisoned mutex!  This anytime result cannot recover.");
             None
         }
     }
 
+    /// Waits until `deadline` and then commits to and returns the best option available, unless
+    /// the Anytime becomes final sooner, in which case this resolves with the final value right
+    /// away.
+    ///
+    /// The deadline is measured with this Anytime's clock.  On the system clock it is handed to
+    /// the `DeadlineScheduler::global` timer, so that no executor timer is needed; it stays
+    /// registered (and finalizes the Anytime when it passes) even if the returned future is
+    /// dropped before then.  Clocks that don't keep real time, such as a `MockClock`, wake the
+    /// future when they are advanced instead, and only a future that is still waiting finalizes
+    /// the Anytime.
+    pub async fn result_by(self: &Arc<Self>, deadline: Instant) -> Option<T>
+    where
+        T: Send + Sync + 'static,
+    {
+        self.read_by(ANONYMOUS_CONSUMER, deadline).await
+    }
+
+    /// `result_by` on behalf of `consumer`.
+    pub(crate) async fn read_by(self: &Arc<Self>, consumer: u64, deadline: Instant) -> Option<T>
+    where
+        T: Send + Sync + 'static,
+    {
+        let mut scheduled = false;
+        let due = || {
+            let due = self.clock.now() >= deadline;
+            if due {
+                self.finalize(FinalizeReason::Deadline);
+            }
+            self.is_final() || due
+        };
+        poll_fn(|cx| {
+            if due() {
+                return Poll::Ready(());
+            }
+            self.register_waker(cx.waker());
+            if !self.clock.wake_on_advance(cx.waker()) && !scheduled {
+                DeadlineScheduler::global().register(self, deadline);
+                scheduled = true;
+            }
+            if due() {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        })
+        .await;
+        self.read(consumer, true)
+    }
+
     /// Stores an updated result in this anytime, if possible.
     pub fn update_result(&self, better_result: T) {
-        if let Ok(mut guard) = self.current_best.lock() {
-            if !self.value_locked.load(Ordering::Relaxed) {
-                guard.replace(better_result);
+        self.submit(better_result, None, Producer::ANONYMOUS);
+    }
+
+    /// Stores the best of a batch of candidates (as judged by the quality function, or the last one
+    /// if there isn't one), taking the lock only once.  The other candidates count as rejected.
+    /// Candidates the validator turns away are left out of the running (see
+    /// `AnytimeBuilder::validator`).
+    pub fn update_many(&self, candidates: impl IntoIterator<Item = T>) {
+        self.submit_many(candidates, Producer::ANONYMOUS);
+    }
+
+    /// Rolls back the latest accepted update, restoring the value it replaced (along with its
+    /// quality and confidence), for when a later check shows the "improvement" to be invalid.
+    /// The rollback counts as a new version, and observers see the restored value as an update.
+    ///
+    /// In an ensemble the members the reverted value was combined from are rolled back with it.
+    /// Reverts are counted in `AnytimeStats::reverts` and recorded in the audit trail.
+    ///
+    /// Only the latest update is guaranteed to be revertible (more can be if `recent_capacity` was
+    /// raised).  Returns false, changing nothing, if there's no earlier value or the Anytime can't
+    /// be changed right now (because it is final, say).
+    pub fn revert_to_previous(&self) -> bool {
+        self.revert(Producer::ANONYMOUS)
+    }
+
+    /// Stores an updated result along with the width of its confidence interval, if possible.  If
+    /// a confidence target was configured (see `AnytimeBuilder::confidence_target`) and
+    /// `interval` is within it, the Anytime finalizes itself with this value.
+    pub fn update_with_confidence(&self, better_result: T, interval: f64) {
+        self.submit(better_result, Some(interval), Producer::ANONYMOUS);
+    }
+
+    /// Stores `result` and finalizes this Anytime with it in one step, for when the search is over
+    /// (an optimum was found, or the search space is exhausted), so that consumers stop waiting for
+    /// improvements that won't come.  The Anytime is finalized with `FinalizeReason::Converged`,
+    /// which tells convergence apart from having run out of time.
+    ///
+    /// If `result` is refused (because the Anytime is already final, say) nothing is finalized.
+    /// A result that is skipped for being the same as the current value still finalizes it.
+    pub fn publish_final(&self, result: T) {
+        self.submit_final(result, Producer::ANONYMOUS);
+    }
+
+    /// Returns the quality of the current value, if a quality function was configured (see
+    /// `AnytimeBuilder::quality`) and there is a value.
+    pub fn quality(&self) -> Option<f64> {
+        self.lock_state().ok()?.quality
+    }
+
+    /// Returns the value the latest read (`get_result`, `try_get_result` or `get_result_leased`)
+    /// returned, which is typically the one the consumer is acting on, or None if there hasn't
+    /// been one.  Only tracked if turned on with `AnytimeBuilder::track_last_read`.
+    pub fn last_read(&self) -> Option<T> {
+        let last_read = self.lock_state().ok()?.last_read.as_ref().map(|(v, _)| Arc::clone(v));
+        last_read.as_deref().cloned()
+    }
+
+    /// Returns how much the quality of the current value exceeds that of the value last read (see
+    /// `last_read`), so that a control loop can judge whether switching to the newer answer is
+    /// worth its cost.  None if there hasn't been a read, or there is no quality function (see
+    /// `AnytimeBuilder::quality`).
+    pub fn improvement_over_last_read(&self) -> Option<f64> {
+        let guard = self.lock_state().ok()?;
+        let (_, read_quality) = guard.last_read.as_ref()?;
+        Some(guard.quality? - (*read_quality)?)
+    }
+
+    /// Estimates how long from now it will take for the quality (see `AnytimeBuilder::quality`) to
+    /// reach `score`, by extrapolating the rate at which it rose over the latest few updates.
+    /// Returns zero if it already has, and None if there's no telling: there's no quality
+    /// function, too little history, the quality isn't rising, or the Anytime is final.
+    ///
+    /// This is only as good as the assumption that progress continues at the same pace, which
+    /// anytime algorithms rarely honour for long (their improvements tend to slow down), so treat
+    /// it as an optimistic guess.
+    pub fn estimated_time_to(&self, score: f64) -> Option<Duration> {
+        let guard = self.lock_state().ok()?;
+        if guard.quality? >= score {
+            return Some(Duration::ZERO);
+        }
+        if self.is_final() {
+            return None;
+        }
+        let (&(first_at, first), &(last_at, last)) =
+            (guard.progress.front()?, guard.progress.back()?);
+        let rate = (last - first) / last_at.saturating_duration_since(first_at).as_secs_f64();
+        if !(rate > 0.0 && rate.is_finite()) {
+            return None;
+        }
+        let needed = Duration::try_from_secs_f64((score - last) / rate).ok()?;
+        Some(needed.saturating_sub(self.clock.now().saturating_duration_since(last_at)))
+    }
+
+    /// Returns the record of every accepted update and the producer that supplied it, oldest
+    /// first, once this Anytime is final.  Returns None before then, or if the audit trail wasn't
+    /// turned on (see `AnytimeBuilder::audit_trail`).
+    pub fn audit_trail(&self) -> Option<Vec<AuditEntry>> {
+        if !self.is_final() {
+            return None;
+        }
+        self.lock_state().ok()?.audit.clone()
+    }
+
+    /// Returns why this Anytime became final, or None if it isn't final yet.
+    pub fn finalize_reason(&self) -> Option<FinalizeReason> {
+        self.lock_state().ok()?.finalize_reason.clone()
+    }
+
+    /// Returns whether this Anytime is still improving and, if not, how it came to be final.
+    pub fn state(&self) -> CompletionState {
+        match self.finalize_reason() {
+            Some(reason) => reason.completion_state(),
+            None => CompletionState::Improving,
+        }
+    }
+
+    /// Returns the confidence interval width that came with the current value, if it had one.
+    pub fn confidence(&self) -> Option<f64> {
+        self.lock_state().ok()?.confidence
+    }
+
+    /// Creates a producer handle for this Anytime.  Each handle gets its own `ProducerId`.
+    pub fn improver(self: &Arc<Self>) -> Improver<T> {
+        let id = ProducerId(self.next_producer_id.fetch_add(1, Ordering::Relaxed));
+        Improver::new(Arc::clone(self), Producer { id, ..Producer::ANONYMOUS })
+    }
+
+    /// Makes an improver from one detached from this Anytime (see `Improver::detach`), with the
+    /// identity, priority and weight it had before.
+    pub fn reattach(
+        self: &Arc<Self>,
+        detached: DetachedImprover,
+    ) -> Result<Improver<T>, WrongAnytime> {
+        detached.reattach(self)
+    }
+
+    /// Creates a consumer handle for this Anytime.  Once every reader has been dropped without any
+    /// of them having read the result, an Anytime that isn't final yet is finalized so that its
+    /// producers can stop.
+    pub fn reader(self: &Arc<Self>) -> Reader<T>
+    where
+        T: Send + Sync,
+    {
+        Reader::new(Arc::clone(self))
+    }
+
+    /// Blocks until this Anytime accepts an update newer than `last_seen_version` or becomes final,
+    /// then returns the current version.  Feed the returned version back in on the next call to
+    /// wait for each improvement in turn.
+    pub fn wait_for_improvement(&self, last_seen_version: u64) -> u64 {
+        self.wait_for_improvement_or(last_seen_version, || false)
+    }
+
+    /// Like `wait_for_improvement`, but also gives up once `stop` returns true.  Whatever makes
+    /// `stop` true must then call `wake_waiters`.
+    pub(crate) fn wait_for_improvement_or(
+        &self,
+        last_seen_version: u64,
+        stop: impl Fn() -> bool,
+    ) -> u64 {
+        if let Ok(guard) = self.lock_state() {
+            self.changed
+                .wait_while(metrics::untimed(guard), |s| {
+                    s.version <= last_seen_version && !self.is_final() && !stop()
+                })
+                .map(|s| s.version)
+                .unwrap_or(last_seen_version)
+        } else {
+            error!("Attempted to lock a poisoned mutex!  This anytime result cannot recover.");
+            last_seen_version
+        }
+    }
+
+    /// Arranges for `waker` to be woken the next time this Anytime changes (an update is accepted
+    /// or it becomes final).  Wakers are woken at most once per registration, so register again
+    /// after every wake up if you want to keep listening.
+    ///
+    /// This is the primitive to use when integrating with an executor or event loop that isn't
+    /// built on `Future`s.
+    pub fn register_waker(&self, waker: &Waker) {
+        if let Ok(mut guard) = self.lock_state() {
+            if !guard.wakers.iter().any(|w| w.will_wake(waker)) {
+                guard.wakers.push(waker.clone());
+            }
+        } else {
+            error!("Attempted to lock a poisoned mutex!  This anytime result cannot recover.");
+        }
+    }
+
+    /// Future style readiness check: returns `Poll::Ready` once `is_ready` would return true,
+    /// otherwise registers the context's waker and returns `Poll::Pending`.
+    pub fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<()> {
+        self.drain_receiver();
+        if let Ok(mut guard) = self.lock_state() {
+            if self.is_final() || self.meets_floor(&guard) {
+                return Poll::Ready(());
+            }
+            if !guard.wakers.iter().any(|w| w.will_wake(cx.waker())) {
+                guard.wakers.push(cx.waker().clone());
+            }
+            Poll::Pending
+        } else {
+            error!("Attempted to lock a poisoned mutex!  This anytime result cannot recover.");
+            Poll::Ready(())
+        }
+    }
+
+    /// Makes `receiver` a source of updates without needing a thread to forward them: every
+    /// readiness check and read first drains whatever candidates are waiting in the channel, in
+    /// order, as if they had been passed to `update_result`.  Attaching a receiver replaces any
+    /// previously attached one; a receiver is detached once its senders are all gone.
+    ///
+    /// Note that blocking calls such as `wait_for_improvement` don't drain the channel.
+    pub fn attach_receiver(&self, receiver: Receiver<T>) {
+        if let Ok(mut guard) = self.receiver.lock() {
+            guard.replace(receiver);
+        } else {
+            error!("Attempted to lock a poisoned mutex!  This anytime result cannot recover.");
+        }
+    }
+
+    /// Registers an observer that will be told about every update, rejection and finalization
+    /// from now on.  Keep the returned token if you ever want to remove it again.
+    pub fn add_observer(&self, observer: Box<dyn AnytimeObserver<T>>) -> ObserverToken {
+        let token = ObserverToken(self.next_observer_token.fetch_add(1, Ordering::Relaxed));
+        if let Ok(mut guard) = self.lock_state() {
+            guard.observers.push((token, observer));
+        } else {
+            error!("Attempted to lock a poisoned mutex!  This anytime result cannot recover.");
+        }
+        token
+    }
+
+    /// Creates a consumer view whose first read pins the value for that view alone, while the
+    /// Anytime keeps improving for everybody else.
+    pub fn view(self: &Arc<Self>) -> View<T> {
+        View::new(Arc::clone(self))
+    }
+
+    /// Starts following every improvement from now on, beginning with the current best if there
+    /// is one.  Subscribing doesn't freeze anything.
+    pub fn subscribe(self: &Arc<Self>) -> Subscription<T>
+    where
+        T: Send + 'static,
+    {
+        let token = ObserverToken(self.next_observer_token.fetch_add(1, Ordering::Relaxed));
+        if let Ok(mut guard) = self.lock_state() {
+            let version = guard.version;
+            let feed = Arc::new(Feed::new(guard.recent.back().map(|v| (version, Arc::clone(v)))));
+            guard.feeds.push((token, Arc::clone(&feed)));
+            Subscription::new(Arc::clone(self), feed, token, version)
+        } else {
+            error!("Attempted to lock a poisoned mutex!  This anytime result cannot recover.");
+            Subscription::new(Arc::clone(self), Arc::new(Feed::new(None)), token, 0)
+        }
+    }
+
+    /// Stops filling the queue of the subscription with `token`.
+    pub(crate) fn unsubscribe(&self, token: ObserverToken) {
+        if let Ok(mut guard) = self.lock_state() {
+            guard.feeds.retain(|(t, _)| *t != token);
+        }
+    }
+
+    /// Unregisters an observer, handing it back if it was still registered.
+    pub fn remove_observer(&self, token: ObserverToken) -> Option<Box<dyn AnytimeObserver<T>>> {
+        let mut guard = self.lock_state().ok()?;
+        let index = guard.observers.iter().position(|(t, _)| *t == token)?;
+        Some(guard.observers.remove(index).1)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: Clone> Anytime<T> {
+    /// Locks the state, counting the times that means waiting for somebody else (and timing the
+    /// lock, with the `lock-metrics` feature).
+    ///
+    /// Anytimes start out doing every read under the lock, which is cheapest while nobody else
+    /// wants it.  Once the lock has had to be waited for `CONTENDED_BEFORE_SNAPSHOT` times, the
+    /// Anytime also keeps a snapshot of its version and value behind a read-write lock, and reads
+    /// that change nothing use that, so they neither wait for producers nor hold them up.
+    fn lock_state(&self) -> LockResult<StateGuard<'_, T>> {
+        let started = self.lock_meter.start();
+        let locked = match self.state.try_lock() {
+            Ok(guard) => Ok(guard),
+            Err(TryLockError::Poisoned(poisoned)) => Err(poisoned),
+            Err(TryLockError::WouldBlock) => {
+                let contended = self.contended_locks.fetch_add(1, Ordering::Relaxed) + 1;
+                let locked = self.state.lock();
+                if contended == CONTENDED_BEFORE_SNAPSHOT {
+                    if let Ok(guard) = &locked {
+                        // Switched under the lock, so that no change can miss the snapshot.
+                        self.publish_snapshot(guard);
+                        self.snapshot_reads.store(true, Ordering::Release);
+                    }
+                }
+                locked
+            }
+        };
+        self.lock_meter.timed(locked, started)
+    }
+
+    /// The version and value as of the latest change, if reads have switched to the snapshot.
+    fn snapshot(&self) -> Option<(u64, Option<Arc<T>>)> {
+        if !self.snapshot_reads.load(Ordering::Acquire) {
+            return None;
+        }
+        Some(self.snapshot.read().unwrap_or_else(PoisonError::into_inner).clone())
+    }
+
+    /// Brings the snapshot up to date with `state`.
+    fn publish_snapshot(&self, state: &State<T>) {
+        let current = (state.version, state.recent.back().cloned());
+        *self.snapshot.write().unwrap_or_else(PoisonError::into_inner) = current;
+    }
+
+    /// The update path shared by every kind of producer.
+    pub(crate) fn submit(&self, candidate: T, confidence: Option<f64>, producer: Producer) {
+        if let Some(staged) = self.prepare(candidate, confidence) {
+            self.offer_unlocked(staged, producer);
+        }
+    }
+
+    /// The update path for a value that finalizes the Anytime once it is stored.
+    pub(crate) fn submit_final(&self, candidate: T, producer: Producer) {
+        if let Some(staged) = self.prepare(candidate, None) {
+            self.offer_unlocked(Staged { converged: true, ..staged }, producer);
+        }
+    }
+
+    /// Validates, fits and stages a single candidate, returning None if it was turned away.
+    fn prepare(&self, candidate: T, confidence: Option<f64>) -> Option<Staged<T>> {
+        if !self.is_valid(&candidate) {
+            return None;
+        }
+        let candidate = self.fit_budget(candidate)?;
+        let quality = match (&self.quality, &self.combine) {
+            (Some(quality), None) => Some(quality(&candidate)),
+            _ => None,
+        };
+        Some(self.stage(candidate, quality, confidence))
+    }
+
+    /// Takes the lock and offers `staged`.
+    fn offer_unlocked(&self, staged: Staged<T>, producer: Producer) {
+        if let Ok(guard) = self.lock_state() {
+            self.offer(guard, staged, producer);
+        } else {
+            error!("Attempted to lock a poisoned mutex!  This anytime result cannot recover.");
+        }
+    }
+
+    /// The batched update path: only the best of `candidates` is offered, the rest are rejected.
+    pub(crate) fn submit_many(&self, candidates: impl IntoIterator<Item = T>, producer: Producer) {
+        let mut candidates: Vec<T> = candidates.into_iter().collect();
+        candidates.retain(|c| self.is_valid(c));
+        let (best, quality) = match &self.quality {
+            Some(quality) => {
+                let scores: Vec<f64> = candidates.iter().map(&**quality).collect();
+                let best = (0..scores.len()).max_by(|&a, &b| scores[a].total_cmp(&scores[b]));
+                (best, best.map(|i| scores[i]).filter(|_| self.combine.is_none()))
+            }
+            None => (candidates.len().checked_sub(1), None),
+        };
+        if let Some(best) = best {
+            let staged = self.fit_budget(candidates.swap_remove(best));
+            let staged = staged.map(|fitted| self.stage(fitted, quality, None));
+            if let Ok(mut guard) = self.lock_state() {
+                candidates.iter().for_each(|c| guard.reject(c));
+                if let Some(staged) = staged {
+                    self.offer(guard, staged, producer);
+                }
             } else {
-                debug!("Attempted to overwrite a locked value.");
+                error!("Attempted to lock a poisoned mutex!  This anytime result cannot recover.");
             }
+        }
+    }
+
+    /// Offers a consumer's fallback value, which only fills the Anytime if it is still empty, or
+    /// if the fallback meets the quality floor and the current value doesn't.
+    fn submit_fallback(&self, baseline: T) {
+        let staged = match self.prepare(baseline, None) {
+            Some(staged) => staged,
+            None => return,
+        };
+        let meets_floor = self.floor().is_none_or(|f| staged.quality.is_some_and(|q| q >= f));
+        if let Ok(mut guard) = self.lock_state() {
+            if self.meets_floor(&guard) || (guard.current_best().is_some() && !meets_floor) {
+                debug!("Discarded a fallback value, a producer got there first.");
+                guard.reject(&staged.value);
+            } else {
+                self.offer(guard, staged, Producer::ANONYMOUS);
+            }
+        } else {
+            error!("Attempted to lock a poisoned mutex!  This anytime result cannot recover.");
+        }
+    }
+
+    /// Runs the validator on `candidate`, counting it as rejected if it's invalid.
+    fn is_valid(&self, candidate: &T) -> bool {
+        let problem = match self.validator.as_ref().map(|validate| validate(candidate)) {
+            Some(Err(problem)) => problem,
+            _ => return true,
+        };
+        debug!("Rejected an invalid update: {}", problem.as_str());
+        if let Ok(mut guard) = self.lock_state() {
+            guard.stats.invalid_updates += 1;
+            guard.reject(candidate);
+        } else {
+            error!("Attempted to lock a poisoned mutex!  This anytime result cannot recover.");
+        }
+        false
+    }
+
+    /// Gives the compactor a chance to shrink a candidate that wouldn't fit in the memory budget,
+    /// returning None if the candidate was discarded instead.
+    fn fit_budget(&self, candidate: T) -> Option<T> {
+        let (bytes, size_of, compact) = match (&self.memory_budget, &self.compactor) {
+            (Some((bytes, size_of)), Some(compact)) => (*bytes, size_of, compact),
+            _ => return Some(candidate),
+        };
+        let room = self.lock_state().map(|s| s.room(bytes)).unwrap_or(0);
+        if size_of(&candidate) <= room {
+            return Some(candidate);
+        }
+        let compacted = compact(&candidate);
+        if compacted.is_none() {
+            debug!("Discarded an update that doesn't fit in the memory budget.");
+            self.reject_outright(&candidate);
+        }
+        compacted
+    }
+
+    /// Counts a candidate as rejected without offering it.
+    fn reject_outright(&self, candidate: &T) {
+        if let Ok(mut guard) = self.lock_state() {
+            guard.reject(candidate);
         } else {
             error!("Attempted to lock a poisoned mutex!  This anytime result cannot recover.");
         }
     }
+
+    /// Does as much of the work of an update as possible before the lock is taken, so that slow
+    /// quality functions and large values don't hold up readers.
+    fn stage(&self, value: T, quality: Option<f64>, confidence: Option<f64>) -> Staged<T> {
+        let size = self.memory_budget.as_ref().map_or(0, |(_, size_of)| size_of(&value));
+        let now = self.clock.now();
+        Staged { value: Arc::new(value), quality, confidence, size, now, converged: false }
+    }
+
+    /// Accepts or rejects one staged candidate, given the lock.
+    fn offer(&self, mut guard: StateGuard<'_, T>, staged: Staged<T>, producer: Producer) {
+        let Staged { value, mut quality, confidence, mut size, now, converged } = staged;
+        if guard.producers_seen.insert(producer.id) {
+            guard.stats.distinct_producers += 1;
+        }
+        if guard.grace_until.is_some_and(|until| now >= until) {
+            debug!("Rejected an update that arrived after the read barrier's grace window.");
+            guard.reject(&value);
+            self.freeze(guard, FinalizeReason::Read);
+            return;
+        }
+        // An ensemble member's size is checked once it has been combined, below.
+        let refusal = self.refusal(&guard, now, producer).or_else(|| {
+            let (bytes, _) = self.memory_budget.as_ref().filter(|_| self.combine.is_none())?;
+            Some("Rejected an update that doesn't fit in the memory budget.")
+                .filter(|_| size > guard.room(*bytes))
+        });
+        if let Some(refusal) = refusal {
+            debug!("{}", refusal);
+            guard.reject(&value);
+            // Release the lock before the rejected value is dropped.
+            drop(guard);
+            return;
+        }
+        let outranks = guard.top_priority.is_some_and(|top| producer.priority > top);
+        guard.lease_until = None;
+        // Ensembles can only be combined once every member is at hand, under the lock.
+        let (value, members) = match &self.combine {
+            Some(combine) => {
+                let mut members = guard.ensembles.back().cloned().unwrap_or_default();
+                members.insert(producer.id, (Arc::clone(&value), producer.weight));
+                let weighted: Vec<_> = members.values().map(|(c, w)| (&**c, *w)).collect();
+                let combined = combine(&weighted);
+                if let Some((bytes, size_of)) = &self.memory_budget {
+                    size = size_of(&combined);
+                    if size > guard.room(*bytes) {
+                        debug!(
+                            "Rejected an update whose ensemble doesn't fit in the memory budget."
+                        );
+                        guard.reject(&value);
+                        drop(guard);
+                        return;
+                    }
+                }
+                quality = self.quality.as_ref().map(|quality| quality(&combined));
+                (Arc::new(combined), Some(members))
+            }
+            None => (value, None),
+        };
+        let duplicate = self.same_as.as_ref().is_some_and(|same_as| {
+            guard.confidence == confidence
+                && guard.current_best().is_some_and(|current| same_as(current, &value))
+        });
+        if duplicate {
+            guard.stats.duplicate_updates += 1;
+            // The combined value didn't change, but the member did.
+            if let (Some(members), Some(current)) = (members, guard.ensembles.back_mut()) {
+                *current = members;
+            }
+            if converged {
+                self.freeze(guard, FinalizeReason::Converged);
+            } else {
+                // Release the lock before the duplicate is dropped.
+                drop(guard);
+            }
+            return;
+        }
+        // A surrendering read may have arrived while this update was being prepared.
+        if self.read_underway() {
+            debug!("Gave way to a read that arrived during an update.");
+            guard.reject(&value);
+            drop(guard);
+            return;
+        }
+        // Only updates that would be stored are checked.  A producer that outranks the previous
+        // one may override it with whatever it likes.
+        if cfg!(debug_assertions) && self.combine.is_none() && !outranks {
+            if let (Some(old), Some(new)) = (guard.quality, quality) {
+                if new < old {
+                    drop(guard);
+                    panic!(
+                        "{} lowered the quality of an Anytime from {} to {}.",
+                        producer.id, old, new
+                    );
+                }
+            }
+        }
+        // Only a stored update locks lower priority producers out.
+        guard.top_priority = guard.top_priority.max(Some(producer.priority));
+        let previous = guard.quality.zip(guard.updated_at);
+        let superseded = guard.accept(value, members, Accepted { size, quality, confidence }, now);
+        self.has_value.store(true, Ordering::Relaxed);
+        let version = guard.version;
+        if let Some(audit) = &mut guard.audit {
+            audit.push(AuditEntry {
+                version,
+                producer: producer.id,
+                accepted_at: now,
+                quality,
+                reverted: false,
+            });
+        }
+        guard.confidence = confidence;
+        guard.quality = quality;
+        let stop = if converged {
+            Some(FinalizeReason::Converged)
+        } else {
+            self.stop_reason(confidence, previous, quality, now)
+        };
+        match stop {
+            Some(reason) => {
+                self.freeze(guard, reason);
+            }
+            None => self.announce_change(guard),
+        }
+        self.recycle(superseded);
+    }
+
+    /// Hands a value that fell out of the Anytime to the recycler, unless a read is still cloning
+    /// it.
+    fn recycle(&self, old: Option<Arc<T>>) {
+        if let (Some(recycler), Some(old)) = (&self.recycler, old.and_then(Arc::into_inner)) {
+            recycler(old);
+        }
+    }
+
+    /// Remembers the current value as the one last handed to a consumer, if the Anytime keeps
+    /// track (see `AnytimeBuilder::track_last_read`).
+    fn note_read(&self, state: &mut State<T>) {
+        if self.track_last_read {
+            state.last_read = state.recent.back().map(|v| (Arc::clone(v), state.quality));
+        }
+    }
+
+    /// `note_read` for a read that didn't need the lock because the Anytime is final.
+    fn note_final_read(&self) {
+        if self.track_last_read {
+            if let Ok(mut guard) = self.lock_state() {
+                self.note_read(&mut guard);
+            }
+        }
+    }
+
+    /// The quality floor set with `set_minimum_quality`, if there is one.
+    fn floor(&self) -> Option<f64> {
+        Some(f64::from_bits(self.minimum_quality.load(Ordering::Relaxed))).filter(|f| !f.is_nan())
+    }
+
+    /// Returns true if there is a current value and it meets the quality floor, if any.
+    fn meets_floor(&self, state: &State<T>) -> bool {
+        state.current_best().is_some()
+            && self.floor().is_none_or(|floor| state.quality.is_some_and(|q| q >= floor))
+    }
+
+    /// Counts a read by `consumer` against the read barrier, returning true if the read gets past
+    /// it and may freeze the Anytime.
+    fn passes_barrier(&self, guard: &mut State<T>, consumer: u64) -> bool {
+        match self.read_barrier {
+            None => true,
+            Some(ReadBarrier::Consumers(needed)) => {
+                guard.consumers_seen.insert(consumer);
+                guard.consumers_seen.len() >= needed
+            }
+            Some(ReadBarrier::Grace(window)) => {
+                let now = self.clock.now();
+                now >= *guard.grace_until.get_or_insert(now + window)
+            }
+        }
+    }
+
+    /// Copies the current best out of the state, cloning it only after the lock is released if
+    /// the Anytime was configured to (see `AnytimeBuilder::clone_outside_lock`).
+    fn copy_out(&self, guard: StateGuard<'_, T>) -> Option<T> {
+        if self.clone_outside_lock {
+            let snapshot = guard.recent.back().cloned();
+            drop(guard);
+            snapshot.as_deref().cloned()
+        } else {
+            guard.current_best().cloned()
+        }
+    }
+
+    /// Holds updates off for as long as the returned guard lives, if reads surrender.
+    fn surrender(&self) -> Option<Surrender<'_>> {
+        if self.read_policy != ReadPolicy::Surrender {
+            return None;
+        }
+        self.surrendering_reads.fetch_add(1, Ordering::SeqCst);
+        Some(Surrender(&self.surrendering_reads))
+    }
+
+    /// Returns true if an update that hasn't been stored yet must give way to a read.
+    fn read_underway(&self) -> bool {
+        self.value_locked.load(Ordering::Relaxed)
+            || self.surrendering_reads.load(Ordering::SeqCst) > 0
+    }
+
+    /// Why `producer` may not change the value right now, if it may not.
+    fn refusal(&self, guard: &State<T>, now: Instant, producer: Producer) -> Option<&'static str> {
+        if self.read_underway() {
+            Some("Attempted to overwrite a locked value.")
+        } else if guard.lease_until.is_some_and(|until| now < until) {
+            Some("Attempted to overwrite a leased value.")
+        } else if guard.top_priority.is_some_and(|p| producer.priority < p) {
+            Some("Ignored an update from a lower priority producer.")
+        } else {
+            None
+        }
+    }
+
+    /// The rollback path shared by every kind of producer.
+    pub(crate) fn revert(&self, producer: Producer) -> bool {
+        let mut guard = match self.lock_state() {
+            Ok(guard) => guard,
+            Err(_) => {
+                error!("Attempted to lock a poisoned mutex!  This anytime result cannot recover.");
+                return false;
+            }
+        };
+        let now = self.clock.now();
+        let refusal = self.refusal(&guard, now, producer).or_else(|| {
+            Some("There's no earlier value to revert to.").filter(|_| guard.recent.len() < 2)
+        });
+        if let Some(refusal) = refusal {
+            debug!("{}", refusal);
+            return false;
+        }
+        let reverted = guard.restore_previous(now);
+        let (version, quality) = (guard.version, guard.quality);
+        if let Some(audit) = &mut guard.audit {
+            audit.push(AuditEntry {
+                version,
+                producer: producer.id,
+                accepted_at: now,
+                quality,
+                reverted: true,
+            });
+        }
+        self.announce_change(guard);
+        self.recycle(reverted);
+        true
+    }
+
+    /// Wakes the threads blocked waiting for this Anytime without it having changed, so that they
+    /// check their other reasons to stop waiting.
+    pub(crate) fn wake_waiters(&self) {
+        let _guard = self.lock_state();
+        self.changed.notify_all();
+    }
+
+    /// Runs `hook` once this Anytime is final, or right away if it already is.  Unlike observers,
+    /// hooks run after the lock has been let go, so they may lock other Anytimes that could in
+    /// turn lock this one (a link back, a pipeline waking its thread) without deadlocking.
+    pub(crate) fn on_finalized(&self, hook: impl FnOnce(&FinalizeReason) + Send + 'static) {
+        if let Ok(mut guard) = self.lock_state() {
+            match guard.finalize_reason.clone() {
+                Some(reason) => {
+                    drop(guard);
+                    hook(&reason);
+                }
+                None => guard.finalize_hooks.push(Box::new(hook)),
+            }
+        } else {
+            error!("Attempted to lock a poisoned mutex!  This anytime result cannot recover.");
+        }
+    }
+
+    /// Returns the current version and best value without freezing anything.
+    pub(crate) fn peek(&self) -> (u64, Option<T>) {
+        self.drain_receiver();
+        if let Some((version, value)) = self.snapshot() {
+            return (version, value.as_deref().cloned());
+        }
+        if let Ok(guard) = self.lock_state() {
+            (guard.version, self.copy_out(guard))
+        } else {
+            error!("Attempted to lock a poisoned mutex!  This anytime result cannot recover.");
+            (0, None)
+        }
+    }
+
+    /// Submits every candidate waiting in the attached receiver, if there is one.
+    fn drain_receiver(&self) {
+        if let Ok(mut guard) = self.receiver.lock() {
+            while let Some(receiver) = guard.as_ref() {
+                match receiver.try_recv() {
+                    Ok(candidate) => self.submit(candidate, None, Producer::ANONYMOUS),
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => drop(guard.take()),
+                }
+            }
+        }
+    }
+
+    /// Decides whether the update that was just accepted should also end the search.
+    fn stop_reason(
+        &self,
+        confidence: Option<f64>,
+        previous: Option<(f64, Instant)>,
+        quality: Option<f64>,
+        now: Instant,
+    ) -> Option<FinalizeReason> {
+        if let (Some(width), Some(target)) = (confidence, self.confidence_target) {
+            if width <= target {
+                return Some(FinalizeReason::ConfidenceReached);
+            }
+        }
+        // Assume the next step will improve as much, and take as long, as the last one did.
+        let utility = self.utility.as_ref()?;
+        let ((previous_quality, previous_time), quality) = (previous?, quality?);
+        let step = now.saturating_duration_since(previous_time);
+        let elapsed = now.saturating_duration_since(self.created_at);
+        let projected = quality + (quality - previous_quality);
+        let marginal = utility(projected, elapsed + step) - utility(quality, elapsed);
+        if marginal <= 0.0 {
+            Some(FinalizeReason::Unprofitable)
+        } else {
+            None
+        }
+    }
+
+    /// Makes the current value final, returning false if it already was.
+    fn freeze(&self, mut guard: StateGuard<'_, T>, reason: FinalizeReason) -> bool {
+        if self.value_locked.swap(true, Ordering::Relaxed) {
+            return false;
+        }
+        let _ = self.final_value.set(guard.recent.back().cloned());
+        // Nothing can be reverted any more.
+        let leftover = guard.evict_revert_slot();
+        let lifetime = self.clock.now().saturating_duration_since(self.created_at);
+        guard.stats.time_to_finalization = Some(lifetime);
+        guard.notify_finalize(&reason);
+        guard.finalize_reason = Some(reason.clone());
+        let hooks = mem::take(&mut guard.finalize_hooks);
+        self.announce_change(guard);
+        // Producers holding off while paused should find out that their work is over.
+        pause::wake_waiters();
+        hooks.into_iter().for_each(|hook| hook(&reason));
+        self.recycle(leftover);
+        true
+    }
+
+    /// Wakes everybody waiting for this Anytime to change, releasing the lock first.
+    fn announce_change(&self, mut guard: StateGuard<'_, T>) {
+        if self.snapshot_reads.load(Ordering::Acquire) {
+            self.publish_snapshot(&guard);
+        }
+        let wakers = mem::take(&mut guard.wakers);
+        drop(guard);
+        self.changed.notify_all();
+        wakers.into_iter().for_each(Waker::wake);
+    }
+}
+
+/// The type erased view of an Anytime used by the crate's background machinery.
+#[cfg(feature = "std")]
+pub(crate) trait Finalizable: Send + Sync {
+    /// Freezes the current value, returning false if it was already final.
+    fn finalize(&self, reason: FinalizeReason) -> bool;
+
+    /// See `Anytime::is_final`.
+    fn is_final(&self) -> bool;
+
+    /// See `Anytime::register_waker`.
+    fn register_waker(&self, waker: &Waker);
+}
+
+#[cfg(feature = "std")]
+impl<T: Clone + Send + Sync> Finalizable for Anytime<T> {
+    fn is_final(&self) -> bool {
+        Anytime::is_final(self)
+    }
+
+    fn register_waker(&self, waker: &Waker) {
+        Anytime::register_waker(self, waker)
+    }
+
+    fn finalize(&self, reason: FinalizeReason) -> bool {
+        self.finalize_with(reason)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: Clone> Anytime<T> {
+    /// Freezes the current value, returning false if it was already final.  This is
+    /// `Finalizable::finalize` for Anytimes whose values aren't `Send + Sync`.
+    pub(crate) fn finalize_with(&self, reason: FinalizeReason) -> bool {
+        match self.lock_state() {
+            Ok(guard) => self.freeze(guard, reason),
+            Err(poisoned) => {
+                // A producer panicked under the lock, which is when its producers most need to
+                // be told to stop, so finalize anyway.  The panic may well have come from one of
+                // the observers, so they aren't told.  Once final the state never changes again,
+                // so it is safe to read from then on.
+                error!("Finalizing an Anytime whose mutex was poisoned by a panicking producer.");
+                let mut guard = poisoned.into_inner();
+                guard.observers.clear();
+                let froze = self.freeze(guard, reason);
+                self.state.clear_poison();
+                froze
+            }
+        }
+    }
+}
+
+/// Marks a read with `ReadPolicy::Surrender` as underway while it lives.
+#[cfg(feature = "std")]
+struct Surrender<'a>(&'a AtomicUsize);
+
+#[cfg(feature = "std")]
+impl Drop for Surrender<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: Clone> Drop for Anytime<T> {
+    fn drop(&mut self) {
+        if self.recycler.is_none() {
+            return;
+        }
+        // Let go of every other reference to the values that are left, then recycle them.
+        drop(self.final_value.take());
+        drop(mem::take(&mut self.snapshot.get_mut().unwrap_or_else(PoisonError::into_inner).1));
+        let state = self.state.get_mut().unwrap_or_else(PoisonError::into_inner);
+        state.last_read = None;
+        let leftovers = mem::take(&mut state.recent);
+        leftovers.into_iter().for_each(|value| self.recycle(Some(value)));
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: Clone> Default for Anytime<T> {
+    fn default() -> Anytime<T> {
+        Anytime::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> State<T> {
+    fn current_best(&self) -> Option<&T> {
+        self.recent.back().map(|v| &**v)
+    }
+
+    /// Stores `candidate` as the new best, returning the value that fell out to make room, if any.
+    fn accept(
+        &mut self,
+        candidate: Arc<T>,
+        members: Option<Members<T>>,
+        meta: Accepted,
+        now: Instant,
+    ) -> Option<Arc<T>> {
+        self.version += 1;
+        self.stats.accepted_updates += 1;
+        self.stats.first_accepted_at.get_or_insert(now);
+        self.stats.last_accepted_at = Some(now);
+        let version = self.version;
+        let previous = self.recent.back().map(|v| &**v);
+        self.observers.iter().for_each(|(_, o)| o.on_replace(previous, &candidate, version));
+        self.feeds.iter().for_each(|(_, feed)| feed.push(version, &candidate));
+        let superseded = self.evict_revert_slot();
+        if let Some(quality) = meta.quality {
+            if self.progress.len() == PROGRESS_WINDOW {
+                self.progress.pop_front();
+            }
+            self.progress.push_back((now, quality));
+        }
+        self.recent.push_back(candidate);
+        self.recent_meta.push_back(meta);
+        self.ensembles.extend(members);
+        self.retained_bytes += meta.size;
+        self.updated_at = Some(now);
+        superseded
+    }
+
+    /// Drops the oldest value if it's only being kept for `Anytime::revert_to_previous` (beyond
+    /// `recent_capacity`), returning it.
+    fn evict_revert_slot(&mut self) -> Option<Arc<T>> {
+        if self.recent.len() <= self.recent_capacity {
+            return None;
+        }
+        self.retained_bytes -= self.recent_meta.pop_front().map_or(0, |m| m.size);
+        if self.ensembles.len() == self.recent.len() {
+            self.ensembles.pop_front();
+        }
+        self.recent.pop_front()
+    }
+
+    /// How many bytes of `budget` a new value could use, counting the space it would free up by
+    /// pushing the oldest recent value out.
+    fn room(&self, budget: usize) -> usize {
+        let freed = if self.recent.len() > self.recent_capacity {
+            self.recent_meta.front().map_or(0, |m| m.size)
+        } else {
+            0
+        };
+        budget.saturating_sub(self.retained_bytes - freed)
+    }
+
+    /// Drops the current best in favour of the value before it, returning the dropped value.
+    fn restore_previous(&mut self, now: Instant) -> Option<Arc<T>> {
+        if self.ensembles.len() == self.recent.len() {
+            self.ensembles.pop_back();
+        }
+        let reverted = self.recent.pop_back();
+        let reverted_meta = self.recent_meta.pop_back();
+        self.retained_bytes -= reverted_meta.map_or(0, |m| m.size);
+        if reverted_meta.is_some_and(|m| m.quality.is_some()) {
+            self.progress.pop_back();
+        }
+        let restored = self.recent_meta.back().copied();
+        self.quality = restored.and_then(|m| m.quality);
+        self.confidence = restored.and_then(|m| m.confidence);
+        self.version += 1;
+        self.stats.reverts += 1;
+        self.updated_at = Some(now);
+        if let Some(current) = self.recent.back() {
+            let version = self.version;
+            let previous = reverted.as_deref();
+            self.observers.iter().for_each(|(_, o)| o.on_replace(previous, current, version));
+            self.feeds.iter().for_each(|(_, feed)| feed.push(version, current));
+        }
+        reverted
+    }
+
+    fn reject(&mut self, candidate: &T) {
+        self.stats.rejected_updates += 1;
+        self.observers.iter().for_each(|(_, o)| o.on_reject(candidate));
+    }
+
+    fn notify_finalize(&self, reason: &FinalizeReason) {
+        self.observers.iter().for_each(|(_, o)| o.on_finalize(reason));
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use std::{future::Future, pin::pin, sync::mpsc, task::Wake, thread};
+    #[cfg(debug_assertions)]
+    use std::{
+        panic::{self, AssertUnwindSafe},
+        sync::Weak,
+    };
+
+    use super::*;
+    use crate::test_support::within;
+
+    /// Holds up the first update, with the Anytime locked, until the release is dropped, telling
+    /// `stalled` once it has.
+    struct Stall {
+        stalled: Mutex<mpsc::Sender<()>>,
+        release: Mutex<Receiver<()>>,
+    }
+
+    impl AnytimeObserver<u32> for Stall {
+        fn on_update(&self, _value: &u32, _version: u64) {
+            let _ = self.stalled.lock().unwrap().send(());
+            let _ = self.release.lock().unwrap().recv();
+        }
+    }
+
+    #[test]
+    fn contended_anytimes_switch_reads_to_the_snapshot() {
+        let anytime = Arc::new(Anytime::new());
+        let (stalled, is_stalled) = mpsc::channel();
+        let (release, released) = mpsc::channel::<()>();
+        anytime.add_observer(Box::new(Stall {
+            stalled: Mutex::new(stalled),
+            release: Mutex::new(released),
+        }));
+        let updater = Arc::clone(&anytime);
+        let update = thread::spawn(move || updater.update_result(1));
+        is_stalled.recv().unwrap();
+        let readers: Vec<_> = (0..CONTENDED_BEFORE_SNAPSHOT)
+            .map(|_| {
+                let reader = Arc::clone(&anytime);
+                thread::spawn(move || reader.version())
+            })
+            .collect();
+        let contended = Arc::clone(&anytime);
+        within(move || {
+            while contended.contended_locks.load(Ordering::Relaxed) < CONTENDED_BEFORE_SNAPSHOT {
+                thread::yield_now();
+            }
+        });
+        drop(release);
+        update.join().unwrap();
+        readers.into_iter().for_each(|reader| assert_eq!(reader.join().unwrap(), 1));
+        assert!(anytime.stats().snapshot_reads);
+        assert_eq!(anytime.snapshot().map(|(version, _)| version), Some(1));
+        anytime.update_result(2);
+        assert_eq!(anytime.version(), 2);
+        assert_eq!(anytime.peek(), (2, Some(2)));
+        assert!(anytime.is_ready());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn only_producers_of_equal_priority_must_keep_improving() {
+        let anytime = Arc::new(Anytime::builder().quality(|v: &u32| f64::from(*v)).build());
+        let (heuristic, exact) = (anytime.improver(), anytime.improver().with_priority(5));
+        heuristic.update_result(10);
+        exact.update_result(3);
+        assert_eq!(anytime.peek(), (2, Some(3)));
+        let lowered = panic::catch_unwind(AssertUnwindSafe(|| exact.update_result(2)));
+        assert!(lowered.is_err());
+    }
+
+    #[test]
+    fn anytimes_are_sync_when_their_values_are() {
+        fn assert_sync<S: Sync>() {}
+        assert_sync::<Anytime<u32>>();
+        assert_sync::<Anytime<Arc<Mutex<std::cell::Cell<u32>>>>>();
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn a_strict_anytime_recovers_from_an_early_read() {
+        let anytime = Anytime::builder().strict(true).build();
+        assert!(panic::catch_unwind(AssertUnwindSafe(|| anytime.get_result())).is_err());
+        anytime.update_result(3);
+        assert!(anytime.is_ready());
+        assert_eq!(anytime.get_result(), Some(3));
+    }
+
+    #[test]
+    fn a_lease_on_nothing_lets_the_fallback_in() {
+        let anytime = Anytime::new();
+        assert_eq!(anytime.get_result_leased(Duration::from_secs(60)), None);
+        assert_eq!(anytime.get_result_or_else_compute(|| 7), Some(7));
+        assert!(anytime.is_final());
+    }
+
+    #[test]
+    fn readers_falling_back_count_as_consumers_of_their_own() {
+        let anytime = Arc::new(Anytime::builder().read_barrier(ReadBarrier::Consumers(2)).build());
+        let (first, second) = (anytime.reader(), anytime.reader());
+        assert_eq!(first.get_result_or_else_compute(|| 1), Some(1));
+        assert!(!anytime.is_final());
+        assert_eq!(second.get_result_or_else_compute(|| 2), Some(1));
+        assert!(anytime.is_final());
+    }
+
+    fn budgeted(bytes: usize) -> AnytimeBuilder<Vec<u8>> {
+        Anytime::builder()
+            .memory_budget(bytes, Vec::len)
+            .compact_with(|v: &Vec<u8>| Some(v[..v.len().min(5)].to_vec()))
+    }
+
+    #[test]
+    fn batches_and_fallbacks_are_compacted() {
+        let batched = budgeted(10).build();
+        batched.update_many(vec![vec![1; 2], vec![1; 20]]);
+        assert_eq!(batched.peek().1, Some(vec![1; 5]));
+        let fallen_back = budgeted(10).build();
+        assert_eq!(fallen_back.get_result_or_else_compute(|| vec![2; 20]), Some(vec![2; 5]));
+    }
+
+    #[test]
+    fn ensembles_that_outgrow_the_budget_are_rejected() {
+        let anytime = Arc::new(
+            budgeted(6)
+                .ensemble(|members| members.iter().flat_map(|(m, _)| m.to_vec()).collect())
+                .build(),
+        );
+        anytime.improver().update_result(vec![1; 4]);
+        anytime.improver().update_result(vec![2; 4]);
+        assert_eq!(anytime.peek(), (1, Some(vec![1; 4])));
+        assert_eq!(anytime.stats().rejected_updates, 1);
+    }
+
+    #[test]
+    fn sharing_without_a_reader_leaves_the_anytime_open() {
+        let (anytime, improver) = Anytime::shared();
+        improver.update_result(1);
+        assert!(!anytime.is_final());
+        let (anytime, improver, reader) = Anytime::shared_with_reader();
+        improver.update_result(2);
+        drop(reader);
+        assert!(anytime.is_final());
+    }
+
+    fn floored() -> Anytime<u32> {
+        let anytime = Anytime::builder().quality(|v: &u32| f64::from(*v)).build();
+        anytime.set_minimum_quality(5.0);
+        anytime.update_result(2);
+        anytime
+    }
+
+    #[test]
+    fn a_fallback_that_meets_the_floor_replaces_a_value_below_it() {
+        assert_eq!(floored().get_result_or_else_compute(|| 6), Some(6));
+        assert_eq!(floored().get_result_or_else_compute(|| 3), None);
+    }
+
+    #[test]
+    fn leased_reads_respect_the_floor() {
+        let anytime = floored();
+        assert_eq!(anytime.get_result_leased(Duration::from_secs(60)), None);
+        anytime.update_result(6);
+        assert_eq!(anytime.get_result_leased(Duration::from_secs(60)), Some(6));
+        anytime.update_result(7);
+        assert_eq!(anytime.peek(), (2, Some(6)));
+    }
+
+    /// Records whether it has been woken.
+    struct Woken(AtomicBool);
+
+    impl Wake for Woken {
+        fn wake(self: Arc<Self>) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn result_by_follows_the_anytimes_clock() {
+        let clock = Arc::new(MockClock::new());
+        let anytime = Arc::new(Anytime::builder().clock(clock.clone()).build());
+        anytime.update_result(4);
+        let woken = Arc::new(Woken(AtomicBool::new(false)));
+        let waker = Waker::from(Arc::clone(&woken));
+        let mut cx = Context::from_waker(&waker);
+        let mut result = pin!(anytime.result_by(clock.now() + Duration::from_secs(60)));
+        assert_eq!(result.as_mut().poll(&mut cx), Poll::Pending);
+        clock.advance(Duration::from_secs(60));
+        assert!(woken.0.load(Ordering::SeqCst));
+        assert_eq!(result.as_mut().poll(&mut cx), Poll::Ready(Some(4)));
+        assert_eq!(anytime.finalize_reason(), Some(FinalizeReason::Deadline));
+    }
+
+    #[test]
+    fn reverting_an_ensemble_reverts_its_members() {
+        let sum = |members: &[(&u32, f64)]| members.iter().map(|(m, _)| **m).sum();
+        let anytime = Arc::new(Anytime::builder().ensemble(sum).build());
+        let (first, second) = (anytime.improver(), anytime.improver());
+        first.update_result(1);
+        second.update_result(10);
+        assert!(second.revert_to_previous());
+        assert_eq!(anytime.peek(), (3, Some(1)));
+        first.update_result(2);
+        assert_eq!(anytime.peek(), (4, Some(2)));
+    }
+
+    #[test]
+    fn reverts_are_counted_and_audited() {
+        let anytime = Anytime::builder().audit_trail(true).quality(|v: &u32| f64::from(*v)).build();
+        anytime.update_result(1);
+        anytime.update_result(2);
+        assert!(anytime.revert_to_previous());
+        assert_eq!(anytime.stats().reverts, 1);
+        assert_eq!(anytime.get_result(), Some(1));
+        let trail = anytime.audit_trail().unwrap();
+        let reverted: Vec<_> = trail.iter().map(|e| (e.version, e.quality, e.reverted)).collect();
+        assert_eq!(
+            reverted,
+            vec![(1, Some(1.0), false), (2, Some(2.0), false), (3, Some(1.0), true)]
+        );
+    }
+
+    #[test]
+    fn every_superseded_value_is_recycled_eventually() {
+        let recycled = Arc::new(Mutex::new(Vec::new()));
+        let bin = Arc::clone(&recycled);
+        let anytime =
+            Anytime::builder().recycler(move |v: u32| bin.lock().unwrap().push(v)).build();
+        (1..=3).for_each(|v| anytime.update_result(v));
+        assert_eq!(*recycled.lock().unwrap(), vec![1]);
+        assert_eq!(anytime.get_result(), Some(3));
+        assert_eq!(*recycled.lock().unwrap(), vec![1, 2]);
+        drop(anytime);
+        assert_eq!(*recycled.lock().unwrap(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn only_stored_updates_raise_the_priority() {
+        let anytime = Arc::new(Anytime::builder().skip_duplicates().build());
+        let (heuristic, exact) = (anytime.improver(), anytime.improver().with_priority(5));
+        heuristic.update_result(3);
+        exact.update_result(3);
+        heuristic.update_result(4);
+        assert_eq!(anytime.peek(), (2, Some(4)));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn refused_updates_are_not_held_to_the_quality_check() {
+        let target: Arc<OnceLock<Weak<Anytime<u32>>>> = Arc::new(OnceLock::new());
+        let hook = Arc::clone(&target);
+        // A surrendering read arrives while the update is being compared with the current value.
+        let read_arrives = move |_: &u32, _: &u32| {
+            if let Some(anytime) = hook.get().and_then(Weak::upgrade) {
+                anytime.surrendering_reads.fetch_add(1, Ordering::SeqCst);
+            }
+            false
+        };
+        let anytime = Arc::new(
+            Anytime::builder()
+                .quality(|v: &u32| f64::from(*v))
+                .read_policy(ReadPolicy::Surrender)
+                .skip_duplicates_by(read_arrives)
+                .build(),
+        );
+        anytime.update_result(5);
+        let _ = target.set(Arc::downgrade(&anytime));
+        anytime.update_result(3);
+        assert_eq!(anytime.peek(), (1, Some(5)));
+    }
+
+    #[test]
+    fn polling_readiness_wakes_on_the_first_update() {
+        let anytime = Anytime::new();
+        let woken = Arc::new(Woken(AtomicBool::new(false)));
+        let waker = Waker::from(Arc::clone(&woken));
+        let mut cx = Context::from_waker(&waker);
+        assert_eq!(anytime.poll_ready(&mut cx), Poll::Pending);
+        anytime.update_result(1);
+        assert!(woken.0.load(Ordering::SeqCst));
+        assert_eq!(anytime.poll_ready(&mut cx), Poll::Ready(()));
+    }
+
+    #[test]
+    fn registered_wakers_are_woken_by_finalization() {
+        let anytime = Anytime::new();
+        anytime.update_result(1);
+        let woken = Arc::new(Woken(AtomicBool::new(false)));
+        anytime.register_waker(&Waker::from(Arc::clone(&woken)));
+        assert_eq!(anytime.get_result(), Some(1));
+        assert!(woken.0.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn waiting_for_improvement_returns_each_newer_version() {
+        let anytime = Arc::new(Anytime::new());
+        anytime.update_result(1);
+        assert_eq!(anytime.wait_for_improvement(0), 1);
+        let producer = Arc::clone(&anytime);
+        let update = thread::spawn(move || producer.update_result(2));
+        let waiter = Arc::clone(&anytime);
+        assert_eq!(within(move || waiter.wait_for_improvement(1)), 2);
+        update.join().unwrap();
+    }
+
+    #[test]
+    fn waiting_for_improvement_ends_when_the_anytime_is_final() {
+        let anytime = Arc::new(Anytime::new());
+        anytime.update_result(1);
+        let reader = Arc::clone(&anytime);
+        let read = thread::spawn(move || reader.get_result());
+        let waiter = Arc::clone(&anytime);
+        assert_eq!(within(move || waiter.wait_for_improvement(1)), 1);
+        assert_eq!(read.join().unwrap(), Some(1));
+    }
+
+    #[test]
+    fn lower_priority_producers_are_ignored_once_a_higher_one_has_published() {
+        let anytime = Arc::new(Anytime::new());
+        let (heuristic, exact) = (anytime.improver(), anytime.improver().with_priority(5));
+        heuristic.update_result(1);
+        exact.update_result(2);
+        heuristic.update_result(3);
+        assert_eq!(anytime.peek(), (2, Some(2)));
+        assert_eq!(anytime.stats().rejected_updates, 1);
+        exact.update_result(4);
+        assert_eq!(anytime.peek(), (3, Some(4)));
+    }
+
+    #[test]
+    fn leased_values_hold_still_until_the_lease_runs_out() {
+        let clock = Arc::new(MockClock::new());
+        let anytime = Anytime::builder().clock(clock.clone()).build();
+        anytime.update_result(1);
+        assert_eq!(anytime.get_result_leased(Duration::from_secs(10)), Some(1));
+        anytime.update_result(2);
+        assert_eq!(anytime.peek(), (1, Some(1)));
+        clock.advance(Duration::from_secs(10));
+        anytime.update_result(3);
+        assert_eq!(anytime.peek(), (2, Some(3)));
+        assert!(!anytime.is_final());
+    }
+
+    #[test]
+    fn recent_keeps_the_latest_accepted_values() {
+        let anytime = Anytime::new();
+        assert!(anytime.recent().is_empty());
+        (1..=3).for_each(|v| anytime.update_result(v));
+        assert_eq!(anytime.recent(), vec![3]);
+        let anytime = Anytime::builder().recent_capacity(3).build();
+        (1..=4).for_each(|v| anytime.update_result(v));
+        assert_eq!(anytime.recent(), vec![2, 3, 4]);
+        assert!(!anytime.is_final());
+    }
+
+    #[test]
+    fn values_are_recycled_once_they_fall_out_of_the_recent_values() {
+        let recycled = Arc::new(Mutex::new(Vec::new()));
+        let bin = Arc::clone(&recycled);
+        let anytime = Anytime::builder()
+            .recent_capacity(2)
+            .recycler(move |v: u32| bin.lock().unwrap().push(v))
+            .build();
+        (1..=3).for_each(|v| anytime.update_result(v));
+        assert!(recycled.lock().unwrap().is_empty());
+        (4..=5).for_each(|v| anytime.update_result(v));
+        assert_eq!(*recycled.lock().unwrap(), vec![1, 2]);
+        assert_eq!(anytime.recent(), vec![4, 5]);
+    }
+
+    #[test]
+    fn ensembles_combine_each_producers_latest_candidate_by_weight() {
+        let weighted_mean = |members: &[(&f64, f64)]| {
+            let total: f64 = members.iter().map(|(_, w)| w).sum();
+            members.iter().map(|(m, w)| **m * w).sum::<f64>() / total
+        };
+        let anytime = Arc::new(Anytime::builder().ensemble(weighted_mean).build());
+        let (light, heavy) = (anytime.improver(), anytime.improver().with_weight(3.0));
+        assert_eq!(heavy.weight(), 3.0);
+        light.update_result(4.0);
+        assert_eq!(anytime.peek(), (1, Some(4.0)));
+        heavy.update_result(8.0);
+        assert_eq!(anytime.peek(), (2, Some(7.0)));
+        light.update_result(0.0);
+        assert_eq!(anytime.peek(), (3, Some(6.0)));
+    }
+
+    #[test]
+    fn reaching_the_confidence_target_finalizes_the_anytime() {
+        let anytime = Anytime::builder().confidence_target(0.1).build();
+        anytime.update_with_confidence(1.0, 0.5);
+        assert_eq!(anytime.confidence(), Some(0.5));
+        assert!(!anytime.is_final());
+        anytime.update_with_confidence(1.2, 0.1);
+        assert_eq!(anytime.finalize_reason(), Some(FinalizeReason::ConfidenceReached));
+        anytime.update_with_confidence(1.1, 0.01);
+        assert_eq!(anytime.get_result(), Some(1.2));
+    }
+
+    #[test]
+    fn unprofitable_searches_stop_themselves() {
+        let clock = Arc::new(MockClock::new());
+        // Every second of computation costs half a unit of quality.
+        let anytime = Anytime::builder()
+            .clock(clock.clone())
+            .quality(|v: &f64| *v)
+            .stop_when_unprofitable(|quality, elapsed| quality - elapsed.as_secs_f64() / 2.0)
+            .build();
+        anytime.update_result(1.0);
+        clock.advance(Duration::from_secs(1));
+        anytime.update_result(2.0);
+        assert!(!anytime.is_final());
+        clock.advance(Duration::from_secs(1));
+        anytime.update_result(2.25);
+        assert_eq!(anytime.finalize_reason(), Some(FinalizeReason::Unprofitable));
+        assert_eq!(anytime.get_result(), Some(2.25));
+    }
+
+    #[test]
+    fn profitability_needs_a_quality_function() {
+        let clock = Arc::new(MockClock::new());
+        let anytime =
+            Anytime::builder().clock(clock.clone()).stop_when_unprofitable(|_, _| 0.0).build();
+        anytime.update_result(1.0);
+        clock.advance(Duration::from_secs(1));
+        anytime.update_result(1.0);
+        assert!(!anytime.is_final());
+    }
+
+    #[test]
+    fn attached_receivers_are_drained_before_answering() {
+        let anytime = Anytime::new();
+        let (sender, receiver) = mpsc::channel();
+        anytime.attach_receiver(receiver);
+        assert!(!anytime.is_ready());
+        sender.send(1).unwrap();
+        sender.send(2).unwrap();
+        assert!(anytime.is_ready());
+        assert_eq!(anytime.peek(), (2, Some(2)));
+        drop(sender);
+        assert_eq!(anytime.get_result(), Some(2));
+        assert!(anytime.receiver.lock().unwrap().is_none());
+    }
+
+    #[test]
+    fn batches_store_only_their_best_candidate() {
+        let anytime = Anytime::builder().quality(|v: &u32| f64::from(*v)).build();
+        anytime.update_many([3, 7, 5]);
+        assert_eq!(anytime.peek(), (1, Some(7)));
+        assert_eq!(anytime.stats().rejected_updates, 2);
+        let anytime = Anytime::new();
+        anytime.update_many([3, 7, 5]);
+        assert_eq!(anytime.peek(), (1, Some(5)));
+        anytime.update_many([]);
+        assert_eq!(anytime.version(), 1);
+    }
+
+    #[test]
+    fn reads_dont_wait_for_updates_being_staged() {
+        let (staging, is_staging) = mpsc::channel();
+        let (release, released) = mpsc::channel::<()>();
+        let (staging, released) = (Mutex::new(staging), Mutex::new(released));
+        // Measuring the second update takes until it is released.
+        let slow = move |v: &u32| {
+            if *v == 2 {
+                let _ = staging.lock().unwrap().send(());
+                let _ = released.lock().unwrap().recv();
+            }
+            f64::from(*v)
+        };
+        let anytime = Arc::new(Anytime::builder().quality(slow).build());
+        anytime.update_result(1);
+        let updater = Arc::clone(&anytime);
+        let update = thread::spawn(move || updater.update_result(2));
+        is_staging.recv().unwrap();
+        let reader = Arc::clone(&anytime);
+        assert_eq!(within(move || reader.peek()), (1, Some(1)));
+        drop(release);
+        update.join().unwrap();
+        assert_eq!(anytime.peek(), (2, Some(2)));
+    }
+
+    #[test]
+    fn trying_to_read_nothing_leaves_the_anytime_open() {
+        let anytime = Anytime::builder().strict(true).build();
+        assert_eq!(anytime.try_get_result(), Err(NotReady));
+        assert!(!anytime.is_final());
+        anytime.update_result(3);
+        assert_eq!(anytime.try_get_result(), Ok(3));
+        assert_eq!(anytime.finalize_reason(), Some(FinalizeReason::Read));
+    }
+
+    #[test]
+    fn final_values_are_read_without_the_lock() {
+        let anytime = Arc::new(Anytime::new());
+        anytime.update_result(1);
+        assert_eq!(anytime.get_result(), Some(1));
+        let locked = anytime.lock_state().unwrap();
+        let reader = Arc::clone(&anytime);
+        assert_eq!(within(move || (reader.get_result(), reader.is_final())), (Some(1), true));
+        drop(locked);
+    }
+
+    #[test]
+    fn reverting_restores_the_value_before_the_latest_update() {
+        let anytime = Anytime::builder().quality(|v: &f64| *v).build();
+        assert!(!anytime.revert_to_previous());
+        anytime.update_with_confidence(1.0, 0.5);
+        assert!(!anytime.revert_to_previous());
+        anytime.update_with_confidence(2.0, 0.25);
+        assert!(anytime.revert_to_previous());
+        assert_eq!(anytime.peek(), (3, Some(1.0)));
+        assert_eq!((anytime.quality(), anytime.confidence()), (Some(1.0), Some(0.5)));
+        assert!(!anytime.revert_to_previous());
+        anytime.update_result(3.0);
+        assert_eq!(anytime.get_result(), Some(3.0));
+        assert!(!anytime.revert_to_previous());
+        assert_eq!(anytime.peek(), (4, Some(3.0)));
+    }
+
+    #[test]
+    fn keeping_more_recent_values_allows_reverting_further() {
+        let anytime = Anytime::builder().recent_capacity(3).build();
+        (1..=3).for_each(|v| anytime.update_result(v));
+        assert!(anytime.revert_to_previous());
+        assert!(anytime.revert_to_previous());
+        assert_eq!(anytime.peek(), (5, Some(1)));
+        assert!(!anytime.revert_to_previous());
+    }
+
+    #[test]
+    fn audit_trails_attribute_every_accepted_update() {
+        let clock = Arc::new(MockClock::new());
+        let anytime = Arc::new(Anytime::builder().clock(clock.clone()).audit_trail(true).build());
+        let (first, second) = (anytime.improver(), anytime.improver());
+        let started = clock.now();
+        first.update_result(1);
+        clock.advance(Duration::from_secs(1));
+        second.update_result(2);
+        anytime.update_result(3);
+        assert_eq!(anytime.audit_trail(), None);
+        assert_eq!(anytime.get_result(), Some(3));
+        second.update_result(4);
+        let trail = anytime.audit_trail().unwrap();
+        let attributed: Vec<_> =
+            trail.iter().map(|e| (e.version, e.producer, e.accepted_at)).collect();
+        let later = started + Duration::from_secs(1);
+        assert_eq!(
+            attributed,
+            vec![
+                (1, first.id(), started),
+                (2, second.id(), later),
+                (3, ProducerId::ANONYMOUS, later)
+            ]
+        );
+    }
+
+    #[test]
+    fn audit_trails_are_off_by_default() {
+        let anytime = Anytime::new();
+        anytime.update_result(1);
+        assert_eq!(anytime.get_result(), Some(1));
+        assert_eq!(anytime.audit_trail(), None);
+    }
+
+    #[test]
+    fn invalid_updates_are_rejected_and_counted() {
+        let even =
+            |v: &u32| if v.is_multiple_of(2) { Ok(()) } else { Err(format!("{} is odd", v)) };
+        let anytime = Anytime::builder().validator(even).build();
+        anytime.update_result(2);
+        anytime.update_result(3);
+        anytime.update_many([5, 4, 7]);
+        assert_eq!(anytime.peek(), (2, Some(4)));
+        let stats = anytime.stats();
+        assert_eq!((stats.invalid_updates, stats.rejected_updates), (3, 3));
+    }
+
+    #[test]
+    fn duplicate_updates_are_skipped() {
+        let anytime = Arc::new(Anytime::builder().skip_duplicates().build());
+        let subscription = anytime.subscribe();
+        anytime.update_result(1);
+        anytime.update_result(1);
+        assert_eq!(anytime.version(), 1);
+        anytime.update_with_confidence(1, 0.5);
+        assert_eq!(anytime.version(), 2);
+        anytime.update_with_confidence(1, 0.5);
+        anytime.update_result(2);
+        assert_eq!(anytime.get_result(), Some(2));
+        let stats = anytime.stats();
+        assert_eq!(
+            (stats.accepted_updates, stats.duplicate_updates, stats.rejected_updates),
+            (3, 2, 0)
+        );
+        assert_eq!(subscription.collect::<Vec<_>>(), vec![1, 1, 2]);
+    }
+
+    #[test]
+    fn duplicates_can_be_told_apart_by_a_custom_test() {
+        let same_tens = |a: &u32, b: &u32| a / 10 == b / 10;
+        let anytime = Anytime::builder().skip_duplicates_by(same_tens).build();
+        [11, 15, 21].iter().for_each(|&v| anytime.update_result(v));
+        assert_eq!(anytime.peek(), (2, Some(21)));
+        let anytime = Anytime::new();
+        [1, 1].iter().for_each(|&v| anytime.update_result(v));
+        assert_eq!(anytime.version(), 2);
+    }
+
+    #[test]
+    fn readiness_hints_follow_updates_and_finalization() {
+        let anytime = Anytime::new();
+        assert!(!anytime.is_ready_hint());
+        anytime.update_result(1);
+        assert!(anytime.is_ready_hint());
+        let empty = Anytime::<u32>::new();
+        assert_eq!(empty.get_result(), None);
+        assert!(empty.is_ready_hint());
+    }
+
+    #[test]
+    fn surrendering_reads_refuse_updates_in_flight() {
+        let anytime = Arc::new(Anytime::builder().read_policy(ReadPolicy::Surrender).build());
+        let (stalled, is_stalled) = mpsc::channel();
+        let (release, released) = mpsc::channel::<()>();
+        anytime.add_observer(Box::new(Stall {
+            stalled: Mutex::new(stalled),
+            release: Mutex::new(released),
+        }));
+        let updater = Arc::clone(&anytime);
+        let first = thread::spawn(move || updater.update_result(1));
+        is_stalled.recv().unwrap();
+        // The second update has to wait for the first to let go of the lock.
+        let updater = Arc::clone(&anytime);
+        let second = thread::spawn(move || updater.update_result(2));
+        let contended = Arc::clone(&anytime);
+        within(move || {
+            while contended.contended_locks.load(Ordering::Relaxed) == 0 {
+                thread::yield_now();
+            }
+        });
+        let reader = Arc::clone(&anytime);
+        let read = thread::spawn(move || reader.get_result());
+        let surrendering = Arc::clone(&anytime);
+        within(move || {
+            while surrendering.surrendering_reads.load(Ordering::SeqCst) == 0 {
+                thread::yield_now();
+            }
+        });
+        drop(release);
+        first.join().unwrap();
+        second.join().unwrap();
+        assert_eq!(read.join().unwrap(), Some(1));
+        assert_eq!(anytime.peek(), (1, Some(1)));
+        assert_eq!(anytime.stats().rejected_updates, 1);
+    }
+
+    #[test]
+    fn result_by_resolves_as_soon_as_the_anytime_is_final() {
+        let clock = Arc::new(MockClock::new());
+        let anytime = Arc::new(Anytime::builder().clock(clock.clone()).build());
+        let woken = Arc::new(Woken(AtomicBool::new(false)));
+        let waker = Waker::from(Arc::clone(&woken));
+        let mut cx = Context::from_waker(&waker);
+        let mut result = pin!(anytime.result_by(clock.now() + Duration::from_secs(60)));
+        assert_eq!(result.as_mut().poll(&mut cx), Poll::Pending);
+        anytime.publish_final(4);
+        assert!(woken.0.load(Ordering::SeqCst));
+        assert_eq!(result.as_mut().poll(&mut cx), Poll::Ready(Some(4)));
+        assert_eq!(anytime.finalize_reason(), Some(FinalizeReason::Converged));
+    }
+
+    #[test]
+    fn result_by_finalizes_at_a_system_clock_deadline() {
+        let anytime = Arc::new(Anytime::new());
+        anytime.update_result(4);
+        let woken = Arc::new(Woken(AtomicBool::new(false)));
+        let waker = Waker::from(Arc::clone(&woken));
+        let mut cx = Context::from_waker(&waker);
+        let mut result = pin!(anytime.result_by(Instant::now() + Duration::from_millis(20)));
+        assert_eq!(result.as_mut().poll(&mut cx), Poll::Pending);
+        let timer = Arc::clone(&woken);
+        within(move || {
+            while !timer.0.load(Ordering::SeqCst) {
+                thread::yield_now();
+            }
+        });
+        assert_eq!(result.as_mut().poll(&mut cx), Poll::Ready(Some(4)));
+        assert_eq!(anytime.finalize_reason(), Some(FinalizeReason::Deadline));
+    }
+
+    #[test]
+    fn publishing_a_final_result_finalizes_the_anytime() {
+        let anytime = Arc::new(Anytime::new());
+        let (heuristic, exact) = (anytime.improver(), anytime.improver().with_priority(5));
+        exact.update_result(2);
+        heuristic.publish_final(1);
+        assert!(!anytime.is_final());
+        exact.publish_final(3);
+        assert_eq!(anytime.finalize_reason(), Some(FinalizeReason::Converged));
+        assert_eq!(anytime.get_result(), Some(3));
+    }
+
+    #[test]
+    fn publishing_the_current_value_as_final_still_finalizes() {
+        let anytime = Anytime::builder().skip_duplicates().build();
+        anytime.update_result(1);
+        anytime.publish_final(1);
+        assert_eq!(anytime.peek(), (1, Some(1)));
+        assert_eq!(anytime.finalize_reason(), Some(FinalizeReason::Converged));
+    }
+
+    #[test]
+    fn states_tell_convergence_from_freezing_and_timing_out() {
+        let anytime = Anytime::new();
+        assert_eq!(anytime.state(), CompletionState::Improving);
+        anytime.update_result(1);
+        assert_eq!(anytime.state(), CompletionState::Improving);
+        assert_eq!(anytime.get_result(), Some(1));
+        assert_eq!(anytime.state(), CompletionState::FrozenByRead);
+        let converged = Anytime::new();
+        converged.publish_final(1);
+        assert_eq!(converged.state(), CompletionState::Converged);
+        let reasons = [
+            (FinalizeReason::Unprofitable, CompletionState::Converged),
+            (FinalizeReason::Deadline, CompletionState::TimedOut),
+            (FinalizeReason::Abandoned, CompletionState::Cancelled),
+            (FinalizeReason::ProducerPanicked("oops".into()), CompletionState::Failed),
+        ];
+        for (reason, state) in reasons.iter() {
+            let anytime = Anytime::<u32>::new();
+            anytime.finalize(reason.clone());
+            assert_eq!(anytime.state(), *state);
+        }
+    }
+
+    #[test]
+    fn shared_handles_all_point_at_the_same_anytime() {
+        let (anytime, improver, reader) = Anytime::shared_with_reader();
+        improver.update_result(1);
+        assert_eq!(reader.get_result(), Some(1));
+        assert!(anytime.is_final() && !improver.should_continue());
+        let (anytime, improver) = Anytime::builder().strict(true).build_shared();
+        improver.update_result(2);
+        assert_eq!(anytime.try_get_result(), Ok(2));
+    }
+
+    /// Tells a test that a clone has stalled, and waits for the test to release it.
+    type Gate = (mpsc::Sender<()>, Receiver<()>);
+
+    /// A value whose next clone, once armed, holds up until it is released.
+    struct Heavy {
+        value: u32,
+        stall: Arc<Mutex<Option<Gate>>>,
+    }
+
+    impl Clone for Heavy {
+        fn clone(&self) -> Heavy {
+            if let Some((stalled, release)) = self.stall.lock().unwrap().take() {
+                let _ = stalled.send(());
+                let _ = release.recv();
+            }
+            Heavy { value: self.value, stall: Arc::clone(&self.stall) }
+        }
+    }
+
+    #[test]
+    fn slow_clones_can_be_made_outside_the_lock() {
+        let stall = Arc::new(Mutex::new(None));
+        let heavy = |value| Heavy { value, stall: Arc::clone(&stall) };
+        let anytime = Arc::new(Anytime::builder().clone_outside_lock(true).build());
+        anytime.update_result(heavy(1));
+        let (stalled, is_stalled) = mpsc::channel();
+        let (release, released) = mpsc::channel();
+        *stall.lock().unwrap() = Some((stalled, released));
+        let reader = Arc::clone(&anytime);
+        let read = thread::spawn(move || reader.get_result_leased(Duration::ZERO).map(|h| h.value));
+        is_stalled.recv().unwrap();
+        let (updater, update) = (Arc::clone(&anytime), heavy(2));
+        within(move || updater.update_result(update));
+        drop(release);
+        assert_eq!(read.join().unwrap(), Some(1));
+        assert_eq!(anytime.version(), 2);
+    }
+
+    #[test]
+    fn etas_extrapolate_the_rate_of_improvement() {
+        let clock = Arc::new(MockClock::new());
+        let anytime = Anytime::builder().clock(clock.clone()).quality(|v: &f64| *v).build();
+        assert_eq!(anytime.estimated_time_to(1.0), None);
+        anytime.update_result(1.0);
+        assert_eq!(anytime.estimated_time_to(5.0), None);
+        assert_eq!(anytime.estimated_time_to(1.0), Some(Duration::ZERO));
+        clock.advance(Duration::from_secs(2));
+        anytime.update_result(2.0);
+        assert_eq!(anytime.estimated_time_to(5.0), Some(Duration::from_secs(6)));
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(anytime.estimated_time_to(5.0), Some(Duration::from_secs(5)));
+        assert_eq!(anytime.get_result(), Some(2.0));
+        assert_eq!(anytime.estimated_time_to(5.0), None);
+    }
+
+    #[test]
+    fn consumer_barriers_freeze_once_enough_consumers_have_read() {
+        let anytime = Arc::new(Anytime::builder().read_barrier(ReadBarrier::Consumers(2)).build());
+        let (first, second) = (anytime.reader(), anytime.reader());
+        anytime.update_result(1);
+        assert_eq!(first.get_result(), Some(1));
+        anytime.update_result(2);
+        assert_eq!(first.get_result(), Some(2));
+        assert!(!anytime.is_final());
+        anytime.update_result(3);
+        assert_eq!(second.get_result(), Some(3));
+        assert_eq!(anytime.finalize_reason(), Some(FinalizeReason::Read));
+        // Reads made directly through the Anytime count as one consumer.
+        let anytime = Arc::new(Anytime::builder().read_barrier(ReadBarrier::Consumers(2)).build());
+        anytime.update_result(1);
+        assert_eq!(anytime.get_result(), Some(1));
+        assert_eq!(anytime.get_result(), Some(1));
+        assert!(!anytime.is_final());
+        assert_eq!(anytime.reader().get_result(), Some(1));
+        assert!(anytime.is_final());
+    }
+
+    #[test]
+    fn grace_barriers_freeze_once_the_window_after_the_first_read_closes() {
+        let clock = Arc::new(MockClock::new());
+        let grace = ReadBarrier::Grace(Duration::from_secs(10));
+        let anytime = Anytime::builder().clock(clock.clone()).read_barrier(grace).build();
+        anytime.update_result(1);
+        clock.advance(Duration::from_secs(30));
+        assert_eq!(anytime.get_result(), Some(1));
+        clock.advance(Duration::from_secs(5));
+        anytime.update_result(2);
+        assert_eq!(anytime.get_result(), Some(2));
+        assert!(!anytime.is_final());
+        clock.advance(Duration::from_secs(5));
+        anytime.update_result(3);
+        assert_eq!(anytime.finalize_reason(), Some(FinalizeReason::Read));
+        assert_eq!(anytime.get_result(), Some(2));
+    }
+
+    #[test]
+    fn a_read_after_the_grace_window_freezes_the_anytime() {
+        let clock = Arc::new(MockClock::new());
+        let grace = ReadBarrier::Grace(Duration::from_secs(10));
+        let anytime = Anytime::builder().clock(clock.clone()).read_barrier(grace).build();
+        anytime.update_result(1);
+        assert_eq!(anytime.get_result(), Some(1));
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(anytime.get_result(), Some(1));
+        assert!(anytime.is_final());
+    }
+
+    #[test]
+    fn values_below_the_floor_are_not_ready() {
+        let anytime = floored();
+        assert!(!anytime.is_ready());
+        assert_eq!(anytime.try_get_result(), Err(NotReady));
+        assert!(!anytime.is_final());
+        anytime.update_result(5);
+        assert!(anytime.is_ready());
+        assert_eq!(anytime.try_get_result(), Ok(5));
+    }
+
+    #[test]
+    fn reads_below_the_floor_freeze_without_handing_anything_out() {
+        let anytime = floored();
+        assert_eq!(anytime.get_result(), None);
+        assert!(anytime.is_final());
+        assert_eq!(anytime.get_result_anyway(), Some(2));
+        let anytime = floored();
+        anytime.set_minimum_quality(f64::NAN);
+        assert!(anytime.is_ready());
+        assert_eq!(anytime.get_result(), Some(2));
+    }
+
+    #[test]
+    fn lowering_the_floor_wakes_whoever_is_polling() {
+        let anytime = floored();
+        let woken = Arc::new(Woken(AtomicBool::new(false)));
+        let waker = Waker::from(Arc::clone(&woken));
+        assert_eq!(anytime.poll_ready(&mut Context::from_waker(&waker)), Poll::Pending);
+        anytime.set_minimum_quality(1.0);
+        assert!(woken.0.load(Ordering::SeqCst));
+        assert_eq!(anytime.poll_ready(&mut Context::from_waker(&waker)), Poll::Ready(()));
+    }
+
+    #[test]
+    fn the_last_read_value_is_kept_alongside_the_live_best() {
+        let anytime =
+            Anytime::builder().quality(|v: &u32| f64::from(*v)).track_last_read(true).build();
+        anytime.update_result(2);
+        assert_eq!((anytime.last_read(), anytime.improvement_over_last_read()), (None, None));
+        assert_eq!(anytime.get_result_leased(Duration::ZERO), Some(2));
+        anytime.update_result(5);
+        assert_eq!(anytime.last_read(), Some(2));
+        assert_eq!(anytime.improvement_over_last_read(), Some(3.0));
+        assert_eq!(anytime.get_result(), Some(5));
+        assert_eq!(
+            (anytime.last_read(), anytime.improvement_over_last_read()),
+            (Some(5), Some(0.0))
+        );
+    }
+
+    #[test]
+    fn last_reads_are_only_tracked_when_asked_for() {
+        let anytime = Anytime::new();
+        anytime.update_result(1);
+        assert_eq!(anytime.get_result(), Some(1));
+        assert_eq!(anytime.last_read(), None);
+    }
+
+    #[test]
+    fn finalized_anytimes_hand_out_their_value_straight_away() {
+        let anytime = Arc::new(Anytime::finalized(7));
+        assert!(anytime.is_final() && anytime.is_ready());
+        assert_eq!(anytime.finalize_reason(), Some(FinalizeReason::Converged));
+        assert_eq!(anytime.try_get_result(), Ok(7));
+        anytime.update_result(8);
+        assert_eq!(anytime.get_result(), Some(7));
+        assert_eq!(anytime.wait_for_improvement(1), 1);
+    }
+    #[test]
+    fn fallbacks_only_run_when_nothing_is_ready() {
+        let ready = Anytime::new();
+        ready.update_result(1);
+        assert_eq!(ready.get_result_or_else_compute(|| unreachable!()), Some(1));
+        let empty = Anytime::new();
+        assert_eq!(empty.get_result_or_else_compute(|| 2), Some(2));
+        assert!(empty.is_final());
+    }
+
+    #[test]
+    fn fallbacks_are_told_when_their_budget_is_spent() {
+        let clock = Arc::new(MockClock::new());
+        let anytime = Anytime::builder().clock(clock.clone()).build();
+        let result = anytime.get_result_or_else_compute_within(Duration::from_secs(1), |spent| {
+            let mut steps = 0;
+            while !spent() {
+                steps += 1;
+                clock.advance(Duration::from_millis(250));
+            }
+            Some(steps)
+        });
+        assert_eq!(result, Some(4));
+    }
 }
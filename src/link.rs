@@ -0,0 +1,119 @@
+//! Finalizing whole graphs of derived Anytimes together.
+
+use std::sync::{Arc, Weak};
+
+use crate::{Anytime, Finalizable, FinalizeReason};
+
+/// Which way finalization travels along a link between an Anytime and one derived from it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Propagation {
+    /// Finalizing the parent finalizes the child, so a cancelled root stops everything below it.
+    Down,
+    /// Finalizing the child finalizes the parent, so the inputs of a result nobody wants any more
+    /// stop improving too.
+    Up,
+    /// Finalizing either one finalizes the other.
+    Both,
+}
+
+/// Links `child`, an Anytime derived from `parent`, so that finalization propagates between them
+/// as `propagation` says.  Anytimes finalized this way carry `FinalizeReason::Propagated`.
+///
+/// Finalization carries on along further links, so linking every derived Anytime to the one it
+/// came from lets a whole tree be cancelled from its root (or from any node, with
+/// `Propagation::Both`).
+///
+/// Links don't keep either Anytime alive.  If the link propagates down and `parent` is already
+/// final, `child` is finalized right away, and likewise up.
+pub fn link<A, B>(parent: &Arc<Anytime<A>>, child: &Arc<Anytime<B>>, propagation: Propagation)
+where
+    A: Clone + Send + Sync + 'static,
+    B: Clone + Send + Sync + 'static,
+{
+    if propagation != Propagation::Up {
+        forward(parent, child);
+    }
+    if propagation != Propagation::Down {
+        forward(child, parent);
+    }
+}
+
+/// Makes finalizing `from` finalize `to`.
+fn forward<A, B>(from: &Arc<Anytime<A>>, to: &Arc<Anytime<B>>)
+where
+    A: Clone + Send + Sync + 'static,
+    B: Clone + Send + Sync + 'static,
+{
+    let to: Weak<dyn Finalizable> = Arc::downgrade(to) as Weak<Anytime<B>>;
+    // `from` is final by the time this runs, so links back to it stop there.
+    from.on_finalized(move |_| {
+        if let Some(target) = to.upgrade().filter(|target| !target.is_final()) {
+            target.finalize(FinalizeReason::Propagated);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn anytime() -> Arc<Anytime<u32>> {
+        Arc::new(Anytime::new())
+    }
+
+    #[test]
+    fn down_links_finalize_children_but_not_parents() {
+        let (parent, child) = (anytime(), anytime());
+        link(&parent, &child, Propagation::Down);
+        child.get_result();
+        assert!(!parent.is_final());
+        let (parent, child) = (anytime(), anytime());
+        link(&parent, &child, Propagation::Down);
+        parent.get_result();
+        assert_eq!(child.finalize_reason(), Some(FinalizeReason::Propagated));
+    }
+
+    #[test]
+    fn up_links_finalize_parents_but_not_children() {
+        let (parent, child) = (anytime(), anytime());
+        link(&parent, &child, Propagation::Up);
+        parent.get_result();
+        assert!(!child.is_final());
+        let (parent, child) = (anytime(), anytime());
+        link(&parent, &child, Propagation::Up);
+        child.get_result();
+        assert_eq!(parent.finalize_reason(), Some(FinalizeReason::Propagated));
+    }
+
+    #[test]
+    fn both_ways_links_carry_on_through_a_tree() {
+        let (root, middle, left, right) = (anytime(), anytime(), anytime(), anytime());
+        link(&root, &middle, Propagation::Both);
+        link(&middle, &left, Propagation::Both);
+        link(&middle, &right, Propagation::Both);
+        left.get_result();
+        assert_eq!(left.finalize_reason(), Some(FinalizeReason::Read));
+        for other in [&root, &middle, &right] {
+            assert_eq!(other.finalize_reason(), Some(FinalizeReason::Propagated));
+        }
+    }
+
+    #[test]
+    fn linking_to_a_final_anytime_propagates_right_away() {
+        let (parent, child) = (anytime(), anytime());
+        parent.get_result();
+        link(&parent, &child, Propagation::Down);
+        assert_eq!(child.finalize_reason(), Some(FinalizeReason::Propagated));
+    }
+
+    #[test]
+    fn links_dont_keep_anytimes_alive() {
+        let (parent, child) = (anytime(), anytime());
+        link(&parent, &child, Propagation::Both);
+        let weak = Arc::downgrade(&child);
+        drop(child);
+        assert!(weak.upgrade().is_none());
+        parent.get_result();
+        assert!(parent.is_final());
+    }
+}
@@ -0,0 +1,160 @@
+//! Many improving results, keyed, that share one finalization domain.
+
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+use crate::diagnostics::{debug, error};
+
+/// What a consumer's read of one key freezes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FreezeScope {
+    /// Reading a key freezes only that key, the others keep improving.
+    Key,
+    /// Reading any key freezes every key at once.
+    Map,
+}
+
+/// A map of results that improve per key until consumers look at them.
+///
+/// This is for multi-query planners (one improving route per vehicle, say) that would otherwise
+/// juggle a collection of separate Anytimes.  Whether reading one key freezes just that key or the
+/// whole map is decided by the `FreezeScope` given at construction; `get_all` always freezes the
+/// whole map.
+pub struct AnytimeMap<K, V: Clone> {
+    entries: Mutex<HashMap<K, Entry<V>>>,
+    scope: FreezeScope,
+    map_locked: AtomicBool,
+}
+
+struct Entry<V> {
+    current_best: Option<V>,
+    locked: bool,
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> AnytimeMap<K, V> {
+    /// Creates an empty, unlocked map whose reads freeze `scope`.
+    pub fn new(scope: FreezeScope) -> AnytimeMap<K, V> {
+        AnytimeMap {
+            entries: Mutex::new(HashMap::new()),
+            scope,
+            map_locked: AtomicBool::new(false),
+        }
+    }
+
+    /// Returns true iff the whole map has been frozen.
+    pub fn is_map_final(&self) -> bool {
+        self.map_locked.load(Ordering::Relaxed)
+    }
+
+    /// Returns true iff `key` can no longer change.
+    pub fn is_final(&self, key: &K) -> bool {
+        self.is_map_final()
+            || self.entries.lock().map(|e| e.get(key).is_some_and(|e| e.locked)).unwrap_or(false)
+    }
+
+    /// Returns true if a preliminary result has been found for `key`, or if the search for it has
+    /// been called off.
+    pub fn is_ready(&self, key: &K) -> bool {
+        self.is_map_final()
+            || self
+                .entries
+                .lock()
+                .map(|e| e.get(key).is_some_and(|e| e.locked || e.current_best.is_some()))
+                .unwrap_or(false)
+    }
+
+    /// Commits to and returns the best option currently available for `key`, freezing either
+    /// the key or the whole map depending on this map's `FreezeScope`.
+    pub fn get_result(&self, key: &K) -> Option<V> {
+        if let Ok(mut guard) = self.entries.lock() {
+            match self.scope {
+                FreezeScope::Map => self.map_locked.store(true, Ordering::Relaxed),
+                FreezeScope::Key => {
+                    guard
+                        .entry(key.clone())
+                        .or_insert(Entry { current_best: None, locked: false })
+                        .locked = true;
+                }
+            }
+            guard.get(key).and_then(|e| e.current_best.clone())
+        } else {
+            error!("Attempted to lock a poisoned mutex!  This anytime result cannot recover.");
+            None
+        }
+    }
+
+    /// Commits to and returns the best options currently available for every key, freezing the
+    /// whole map.
+    pub fn get_all(&self) -> HashMap<K, V> {
+        if let Ok(guard) = self.entries.lock() {
+            self.map_locked.store(true, Ordering::Relaxed);
+            guard
+                .iter()
+                .filter_map(|(k, e)| e.current_best.clone().map(|v| (k.clone(), v)))
+                .collect()
+        } else {
+            error!("Attempted to lock a poisoned mutex!  This anytime result cannot recover.");
+            HashMap::new()
+        }
+    }
+
+    /// Stores an updated result for `key`, if possible.
+    pub fn update_result(&self, key: K, better_result: V) {
+        if let Ok(mut guard) = self.entries.lock() {
+            let entry = guard.entry(key).or_insert(Entry { current_best: None, locked: false });
+            if !self.map_locked.load(Ordering::Relaxed) && !entry.locked {
+                entry.current_best = Some(better_result);
+            } else {
+                debug!("Attempted to overwrite a locked value.");
+            }
+        } else {
+            error!("Attempted to lock a poisoned mutex!  This anytime result cannot recover.");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reading_a_key_freezes_only_that_key() {
+        let map = AnytimeMap::new(FreezeScope::Key);
+        map.update_result("a", 1);
+        map.update_result("b", 1);
+        assert_eq!(map.get_result(&"a"), Some(1));
+        map.update_result("a", 2);
+        map.update_result("b", 2);
+        assert!(map.is_final(&"a") && !map.is_final(&"b") && !map.is_map_final());
+        assert_eq!(map.get_result(&"a"), Some(1));
+        assert_eq!(map.get_result(&"b"), Some(2));
+    }
+
+    #[test]
+    fn reading_a_key_can_freeze_the_whole_map() {
+        let map = AnytimeMap::new(FreezeScope::Map);
+        map.update_result("a", 1);
+        assert!(!map.is_ready(&"b"));
+        assert_eq!(map.get_result(&"a"), Some(1));
+        map.update_result("b", 1);
+        assert!(map.is_map_final() && map.is_final(&"b") && map.is_ready(&"b"));
+        assert_eq!(map.get_result(&"b"), None);
+    }
+
+    #[test]
+    fn reading_everything_freezes_the_whole_map() {
+        let map = AnytimeMap::new(FreezeScope::Key);
+        map.update_result("a", 1);
+        map.update_result("b", 2);
+        assert_eq!(map.get_all(), HashMap::from([("a", 1), ("b", 2)]));
+        map.update_result("a", 3);
+        assert!(map.is_map_final());
+        assert_eq!(map.get_result(&"a"), Some(1));
+    }
+}
@@ -0,0 +1,209 @@
+//! Timing how long an Anytime's lock is waited for and held.  Nothing is measured unless the
+//! `lock-metrics` feature is enabled, in which case the timings show up in `Anytime::stats`.
+
+use std::sync::{LockResult, MutexGuard};
+
+#[cfg(feature = "lock-metrics")]
+use std::{
+    convert::TryFrom,
+    ops::{Deref, DerefMut},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        PoisonError,
+    },
+    time::{Duration, Instant},
+};
+
+/// How long accesses to an Anytime waited for its lock and then held it, measured in real time
+/// across every access so far.  Only available with the `lock-metrics` feature.
+///
+/// A large `total_wait` compared to `total_hold` means the Anytime is contended; a large
+/// `max_hold` points at slow work done under the lock (expensive clones, observers or quality
+/// functions in an ensemble).
+#[cfg(feature = "lock-metrics")]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LockMetrics {
+    /// How many times the lock was taken.
+    pub acquisitions: u64,
+    /// How long all the acquisitions spent waiting for the lock, together.
+    pub total_wait: Duration,
+    /// The longest any one acquisition waited.
+    pub max_wait: Duration,
+    /// How long the lock was held, over all acquisitions.
+    pub total_hold: Duration,
+    /// The longest the lock was held at a time.
+    pub max_hold: Duration,
+}
+
+/// Collects the timings of one Anytime's lock.
+#[cfg(feature = "lock-metrics")]
+#[derive(Default)]
+pub(crate) struct LockMeter {
+    acquisitions: AtomicU64,
+    wait_nanos: AtomicU64,
+    max_wait_nanos: AtomicU64,
+    hold_nanos: AtomicU64,
+    max_hold_nanos: AtomicU64,
+}
+
+/// Collects nothing, without the `lock-metrics` feature.
+#[cfg(not(feature = "lock-metrics"))]
+pub(crate) struct LockMeter;
+
+/// A locked guard that tells its meter how long it was held once it is let go.
+#[cfg(feature = "lock-metrics")]
+pub(crate) struct Timed<'a, S> {
+    /// Only taken out by `untimed`.
+    guard: Option<MutexGuard<'a, S>>,
+    meter: &'a LockMeter,
+    acquired: Instant,
+}
+
+/// Without the `lock-metrics` feature guards aren't timed.
+#[cfg(not(feature = "lock-metrics"))]
+pub(crate) type Timed<'a, S> = MutexGuard<'a, S>;
+
+/// When an attempt to take the lock started.
+#[cfg(feature = "lock-metrics")]
+pub(crate) type Started = Instant;
+
+/// Without the `lock-metrics` feature, nothing.
+#[cfg(not(feature = "lock-metrics"))]
+pub(crate) struct Started;
+
+#[cfg(feature = "lock-metrics")]
+impl LockMeter {
+    pub(crate) fn new() -> LockMeter {
+        LockMeter::default()
+    }
+
+    /// Marks the start of an attempt to take the lock.
+    pub(crate) fn start(&self) -> Started {
+        Instant::now()
+    }
+
+    /// Records how long taking the lock took and starts timing how long it is held.
+    pub(crate) fn timed<'a, S>(
+        &'a self,
+        locked: LockResult<MutexGuard<'a, S>>,
+        started: Started,
+    ) -> LockResult<Timed<'a, S>> {
+        let acquired = Instant::now();
+        self.acquisitions.fetch_add(1, Ordering::Relaxed);
+        record(&self.wait_nanos, &self.max_wait_nanos, acquired - started);
+        let time = |guard| Timed { guard: Some(guard), meter: self, acquired };
+        match locked {
+            Ok(guard) => Ok(time(guard)),
+            Err(poisoned) => Err(PoisonError::new(time(poisoned.into_inner()))),
+        }
+    }
+
+    /// The timings so far.
+    pub(crate) fn snapshot(&self) -> LockMetrics {
+        let duration = |nanos: &AtomicU64| Duration::from_nanos(nanos.load(Ordering::Relaxed));
+        LockMetrics {
+            acquisitions: self.acquisitions.load(Ordering::Relaxed),
+            total_wait: duration(&self.wait_nanos),
+            max_wait: duration(&self.max_wait_nanos),
+            total_hold: duration(&self.hold_nanos),
+            max_hold: duration(&self.max_hold_nanos),
+        }
+    }
+}
+
+#[cfg(not(feature = "lock-metrics"))]
+impl LockMeter {
+    pub(crate) fn new() -> LockMeter {
+        LockMeter
+    }
+
+    /// Marks the start of an attempt to take the lock.
+    pub(crate) fn start(&self) -> Started {
+        Started
+    }
+
+    /// Passes the lock through untimed.
+    pub(crate) fn timed<'a, S>(
+        &'a self,
+        locked: LockResult<MutexGuard<'a, S>>,
+        _started: Started,
+    ) -> LockResult<Timed<'a, S>> {
+        locked
+    }
+}
+
+/// Stops timing `guard`, for handing it to a condition variable: time spent waiting on one isn't
+/// time spent holding the lock.
+#[cfg(feature = "lock-metrics")]
+pub(crate) fn untimed<S>(mut guard: Timed<'_, S>) -> MutexGuard<'_, S> {
+    guard.guard.take().expect("a timed guard is only untimed once")
+}
+
+/// Stops timing `guard`, which isn't timed without the `lock-metrics` feature.
+#[cfg(not(feature = "lock-metrics"))]
+pub(crate) fn untimed<S>(guard: Timed<'_, S>) -> MutexGuard<'_, S> {
+    guard
+}
+
+/// Adds `elapsed` to a running total and maximum.
+#[cfg(feature = "lock-metrics")]
+fn record(total: &AtomicU64, max: &AtomicU64, elapsed: Duration) {
+    let nanos = u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX);
+    total.fetch_add(nanos, Ordering::Relaxed);
+    max.fetch_max(nanos, Ordering::Relaxed);
+}
+
+#[cfg(feature = "lock-metrics")]
+impl<S> Deref for Timed<'_, S> {
+    type Target = S;
+
+    fn deref(&self) -> &S {
+        self.guard.as_ref().expect("an untimed guard is never used")
+    }
+}
+
+#[cfg(feature = "lock-metrics")]
+impl<S> DerefMut for Timed<'_, S> {
+    fn deref_mut(&mut self) -> &mut S {
+        self.guard.as_mut().expect("an untimed guard is never used")
+    }
+}
+
+#[cfg(feature = "lock-metrics")]
+impl<S> Drop for Timed<'_, S> {
+    fn drop(&mut self) {
+        // Release the lock before bookkeeping.
+        self.guard = None;
+        record(&self.meter.hold_nanos, &self.meter.max_hold_nanos, self.acquired.elapsed());
+    }
+}
+
+#[cfg(all(test, feature = "lock-metrics"))]
+mod tests {
+    use std::thread;
+
+    use super::*;
+    use crate::{Anytime, AnytimeObserver};
+
+    /// Holds the lock for a while on every update.
+    struct Slow;
+
+    impl AnytimeObserver<u32> for Slow {
+        fn on_update(&self, _value: &u32, _version: u64) {
+            thread::sleep(Duration::from_millis(20));
+        }
+    }
+
+    #[test]
+    fn stats_time_how_long_the_lock_is_held() {
+        let anytime = Anytime::new();
+        anytime.add_observer(Box::new(Slow));
+        let before = anytime.stats().lock_metrics;
+        assert!(before.max_hold < Duration::from_millis(20));
+        anytime.update_result(1);
+        let metrics = anytime.stats().lock_metrics;
+        assert!(metrics.acquisitions > before.acquisitions);
+        assert!(metrics.max_hold >= Duration::from_millis(20));
+        assert!(metrics.total_hold >= metrics.max_hold && metrics.total_wait >= metrics.max_wait);
+    }
+}
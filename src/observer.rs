@@ -0,0 +1,122 @@
+//! Observers let other code watch an Anytime without polling it.
+
+use std::sync::Arc;
+
+use crate::FinalizeReason;
+
+/// Receives notifications about the life cycle of an Anytime.
+///
+/// Observers are called while the Anytime is locked, so they should be quick and must not call
+/// back into the Anytime that is notifying them.  Every method has a no-op default, implement only
+/// the ones you care about.
+pub trait AnytimeObserver<T>: Send + Sync {
+    /// Called whenever an update is accepted, `version` is the version the update created.
+    fn on_update(&self, _value: &T, _version: u64) {}
+
+    /// Called whenever an update is accepted, along with the value it replaced (None for the first
+    /// value), for observers that want to know what changed.  The default passes the update on to
+    /// `on_update`; an observer that implements this method gets no `on_update` calls.
+    fn on_replace(&self, _previous: Option<&T>, value: &T, version: u64) {
+        self.on_update(value, version)
+    }
+
+    /// Called whenever an update is refused (for example because the value is already final).
+    fn on_reject(&self, _value: &T) {}
+
+    /// Called exactly once, when the Anytime becomes final.
+    fn on_finalize(&self, _reason: &FinalizeReason) {}
+}
+
+/// Lets a single observer be shared between many Anytimes.
+impl<T, O: AnytimeObserver<T> + ?Sized> AnytimeObserver<T> for Arc<O> {
+    fn on_update(&self, value: &T, version: u64) {
+        (**self).on_update(value, version)
+    }
+
+    fn on_replace(&self, previous: Option<&T>, value: &T, version: u64) {
+        (**self).on_replace(previous, value, version)
+    }
+
+    fn on_reject(&self, value: &T) {
+        (**self).on_reject(value)
+    }
+
+    fn on_finalize(&self, reason: &FinalizeReason) {
+        (**self).on_finalize(reason)
+    }
+}
+
+/// Identifies a registered observer so that it can later be removed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ObserverToken(pub(crate) u64);
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::Anytime;
+
+    /// Writes down every notification it gets.
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<String>>);
+
+    impl AnytimeObserver<u32> for Recorder {
+        fn on_update(&self, value: &u32, version: u64) {
+            self.0.lock().unwrap().push(format!("update {} v{}", value, version));
+        }
+
+        fn on_reject(&self, value: &u32) {
+            self.0.lock().unwrap().push(format!("reject {}", value));
+        }
+
+        fn on_finalize(&self, reason: &FinalizeReason) {
+            self.0.lock().unwrap().push(format!("finalize {:?}", reason));
+        }
+    }
+
+    #[test]
+    fn observers_hear_about_updates_rejections_and_finalization() {
+        let recorder = Arc::new(Recorder::default());
+        let anytime = Anytime::new();
+        anytime.add_observer(Box::new(Arc::clone(&recorder)));
+        anytime.update_result(1);
+        anytime.update_result(2);
+        assert_eq!(anytime.get_result(), Some(2));
+        anytime.update_result(3);
+        let heard = recorder.0.lock().unwrap().clone();
+        assert_eq!(heard, ["update 1 v1", "update 2 v2", "finalize Read", "reject 3"]);
+    }
+
+    #[test]
+    fn removed_observers_hear_nothing_more() {
+        let recorder = Arc::new(Recorder::default());
+        let anytime = Anytime::new();
+        let token = anytime.add_observer(Box::new(Arc::clone(&recorder)));
+        anytime.update_result(1);
+        assert!(anytime.remove_observer(token).is_some());
+        assert!(anytime.remove_observer(token).is_none());
+        anytime.update_result(2);
+        assert_eq!(*recorder.0.lock().unwrap(), ["update 1 v1"]);
+    }
+
+    /// Writes down what each update replaced.
+    #[derive(Default)]
+    struct Differ(Mutex<Vec<(Option<u32>, u32)>>);
+
+    impl AnytimeObserver<u32> for Differ {
+        fn on_replace(&self, previous: Option<&u32>, value: &u32, _version: u64) {
+            self.0.lock().unwrap().push((previous.copied(), *value));
+        }
+    }
+
+    #[test]
+    fn replace_observers_see_the_value_each_update_replaced() {
+        let differ = Arc::new(Differ::default());
+        let anytime = Anytime::new();
+        anytime.add_observer(Box::new(Arc::clone(&differ)));
+        anytime.update_result(1);
+        anytime.update_result(2);
+        assert_eq!(*differ.0.lock().unwrap(), [(None, 1), (Some(1), 2)]);
+    }
+}
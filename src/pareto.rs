@@ -0,0 +1,151 @@
+//! Anytime results with several objectives, where no single candidate need be best.
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Mutex,
+};
+
+use crate::diagnostics::{debug, error};
+
+/// Scores a candidate on every objective, higher being better on each.
+type Objectives<T> = dyn Fn(&T) -> Vec<f64> + Send + Sync;
+
+/// The non-dominated candidates found so far under several objectives (the Pareto front), which
+/// keeps improving until a consumer looks at it, after which it will never change.
+///
+/// A candidate dominates another if it is at least as good on every objective and better on at
+/// least one.  Each update joins the front unless some member dominates it or scores exactly the
+/// same, and pushes out every member it dominates.  Reads return either the whole front, for
+/// consumers that make the trade-off themselves, or the member that a scalarization rates best.
+pub struct AnytimePareto<T: Clone> {
+    front: Mutex<Vec<(T, Vec<f64>)>>,
+    objectives: Box<Objectives<T>>,
+    value_locked: AtomicBool,
+}
+
+impl<T: Clone> AnytimePareto<T> {
+    /// Creates an empty, unlocked front.  `objectives` scores a candidate on each objective,
+    /// higher being better, and must always return the same number of scores.
+    pub fn new(objectives: impl Fn(&T) -> Vec<f64> + Send + Sync + 'static) -> AnytimePareto<T> {
+        AnytimePareto {
+            front: Mutex::new(Vec::new()),
+            objectives: Box::new(objectives),
+            value_locked: AtomicBool::new(false),
+        }
+    }
+
+    /// Returns true iff a consumer somewhere has read the front (thereby freezing it).
+    pub fn is_final(&self) -> bool {
+        self.value_locked.load(Ordering::Relaxed)
+    }
+
+    /// Returns true if a candidate has been found, or if the search has been called off.
+    pub fn is_ready(&self) -> bool {
+        self.is_final() || !self.is_empty()
+    }
+
+    /// Returns how many candidates are on the front.
+    pub fn len(&self) -> usize {
+        self.front.lock().map(|front| front.len()).unwrap_or(0)
+    }
+
+    /// Returns true if nothing has been found yet.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Commits to and returns the front as it currently stands.  After calling this, updating the
+    /// front is a no-op.
+    pub fn get_result(&self) -> Vec<T> {
+        self.freeze(|front| front.iter().map(|(candidate, _)| candidate.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Commits to the front as it currently stands and returns the member that `scalarize` rates
+    /// highest given its scores (a weighted sum, say), or None if the front is empty.
+    pub fn get_best(&self, scalarize: impl Fn(&[f64]) -> f64) -> Option<T> {
+        self.freeze(|front| {
+            front
+                .iter()
+                .map(|(candidate, scores)| (candidate, scalarize(scores)))
+                .max_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map(|(candidate, _)| candidate.clone())
+        })
+        .flatten()
+    }
+
+    /// Offers a candidate to the front, if possible.  The objectives are evaluated before the
+    /// front is locked.
+    pub fn update_result(&self, candidate: T) {
+        let scores = (self.objectives)(&candidate);
+        if let Ok(mut guard) = self.front.lock() {
+            if self.value_locked.load(Ordering::Relaxed) {
+                debug!("Attempted to overwrite a locked value.");
+            } else if !guard.iter().any(|(_, kept)| covers(kept, &scores)) {
+                guard.retain(|(_, kept)| !dominates(&scores, kept));
+                guard.push((candidate, scores));
+            }
+        } else {
+            error!("Attempted to lock a poisoned mutex!  This anytime result cannot recover.");
+        }
+    }
+
+    /// Freezes the front and reads it out with `read`.
+    fn freeze<R>(&self, read: impl FnOnce(&[(T, Vec<f64>)]) -> R) -> Option<R> {
+        if let Ok(guard) = self.front.lock() {
+            self.value_locked.store(true, Ordering::Relaxed);
+            Some(read(&guard))
+        } else {
+            error!("Attempted to lock a poisoned mutex!  This anytime result cannot recover.");
+            None
+        }
+    }
+}
+
+/// Returns true if `a` is at least as good as `b` on every objective.
+fn covers(a: &[f64], b: &[f64]) -> bool {
+    a.iter().zip(b).all(|(a, b)| a >= b)
+}
+
+/// Returns true if `a` is at least as good as `b` on every objective and better on one.
+fn dominates(a: &[f64], b: &[f64]) -> bool {
+    covers(a, b) && a.iter().zip(b).any(|(a, b)| a > b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Trades speed against cost: both are scored higher when smaller.
+    fn plans() -> AnytimePareto<(u32, u32)> {
+        AnytimePareto::new(|&(time, cost): &(u32, u32)| vec![-f64::from(time), -f64::from(cost)])
+    }
+
+    #[test]
+    fn the_front_keeps_only_non_dominated_candidates() {
+        let front = plans();
+        front.update_result((5, 5));
+        front.update_result((2, 8));
+        front.update_result((6, 6));
+        front.update_result((5, 5));
+        assert_eq!(front.len(), 2);
+        front.update_result((4, 4));
+        assert_eq!(front.get_result(), vec![(2, 8), (4, 4)]);
+        front.update_result((1, 1));
+        assert!(front.is_final());
+        assert_eq!(front.get_result(), vec![(2, 8), (4, 4)]);
+    }
+
+    #[test]
+    fn scalarized_reads_return_the_best_member_and_freeze_the_front() {
+        let front = plans();
+        assert!(!front.is_ready());
+        front.update_result((2, 8));
+        front.update_result((4, 4));
+        let fast = |scores: &[f64]| 3.0 * scores[0] + scores[1];
+        assert_eq!(front.get_best(fast), Some((2, 8)));
+        front.update_result((1, 1));
+        assert_eq!(front.get_best(|scores| scores[1]), Some((4, 4)));
+        assert_eq!(plans().get_best(fast), None);
+    }
+}
@@ -0,0 +1,53 @@
+//! Holding all improvement back for a while without giving up on it.
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Condvar, Mutex, PoisonError,
+};
+
+static PAUSED: AtomicBool = AtomicBool::new(false);
+static LOCK: Mutex<()> = Mutex::new(());
+static RESUMED: Condvar = Condvar::new();
+
+/// Asks every producer in the process to hold off until `resume` is called, for when the host
+/// needs its CPU back for a while (thermal limits, a burst of higher priority work).  Nothing is
+/// finalized and no progress is lost.
+///
+/// The crate's step based runners (`AnytimeQueue`, `CooperativeRunner`) and `Pipeline` stop
+/// starting new work while paused.  Other producers should call `Improver::wait_while_paused`
+/// between units of work.
+pub fn pause() {
+    PAUSED.store(true, Ordering::Relaxed);
+}
+
+/// Lets producers held off by `pause` carry on.
+pub fn resume() {
+    let _lock = LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+    PAUSED.store(false, Ordering::Relaxed);
+    RESUMED.notify_all();
+}
+
+/// Returns true between calls to `pause` and `resume`.
+pub fn is_paused() -> bool {
+    PAUSED.load(Ordering::Relaxed)
+}
+
+/// Blocks while paused, unless `stop` returns true.  Whatever makes `stop` true must then call
+/// `wake_waiters`.
+pub(crate) fn wait_while_paused(stop: impl Fn() -> bool) {
+    if !is_paused() {
+        return;
+    }
+    let lock = LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+    drop(
+        RESUMED
+            .wait_while(lock, |_| is_paused() && !stop())
+            .unwrap_or_else(PoisonError::into_inner),
+    );
+}
+
+/// Wakes the threads blocked in `wait_while_paused` so that they check their reasons to stop.
+pub(crate) fn wake_waiters() {
+    let _lock = LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+    RESUMED.notify_all();
+}
@@ -0,0 +1,310 @@
+//! Chaining Anytimes, so that each improvement of one stage refines the next.
+
+use std::{
+    cell::Cell,
+    sync::{Arc, Weak},
+    thread::{self, JoinHandle},
+};
+
+use crate::{
+    link, pause,
+    scope::contain,
+    shutdown::{self, Running},
+    Anytime, Improver, Propagation,
+};
+
+/// Decides whether a newer input is different enough to be worth restarting a stage for.
+type RestartIf<A> = dyn Fn(&A, &A) -> bool + Send + Sync;
+
+/// Feeds the improvements of an upstream Anytime into a stage that refines a downstream one, for
+/// plan-then-schedule and coarse-to-fine compositions.
+///
+/// The stage runs on its own thread.  Each time it is started it is handed the upstream's current
+/// value (read without freezing the upstream) and a `Refinement` to publish through; it should keep
+/// refining while `Refinement::should_continue` says so and then return, after which it is
+/// restarted with the newest upstream value.  By default any upstream improvement interrupts the
+/// stage; `restart_if` provides backpressure for upstreams that improve in many tiny steps.
+///
+/// The thread stops once the downstream Anytime is final, or once the upstream is final and the
+/// stage has refined its final value.  Other than that the two Anytimes are finalized separately,
+/// unless `propagate` links them.
+pub struct Pipeline<A: Clone, B: Clone> {
+    upstream: Arc<Anytime<A>>,
+    downstream: Anytime<B>,
+    restart_if: Option<Box<RestartIf<A>>>,
+    propagation: Option<Propagation>,
+}
+
+impl<A, B> Pipeline<A, B>
+where
+    A: Clone + Send + Sync + 'static,
+    B: Clone + Send + Sync + 'static,
+{
+    /// Starts describing a pipeline that refines `downstream` from the values of `upstream`.
+    pub fn new(upstream: &Arc<Anytime<A>>, downstream: Anytime<B>) -> Pipeline<A, B> {
+        Pipeline { upstream: Arc::clone(upstream), downstream, restart_if: None, propagation: None }
+    }
+
+    /// Only interrupts the stage for upstream values that `restart_if(current_input, newer)` judges
+    /// worth it.  Improvements that don't qualify are still picked up when the stage next returns.
+    pub fn restart_if(
+        mut self,
+        restart_if: impl Fn(&A, &A) -> bool + Send + Sync + 'static,
+    ) -> Pipeline<A, B> {
+        self.restart_if = Some(Box::new(restart_if));
+        self
+    }
+
+    /// Links the upstream (as parent) and downstream Anytimes, see `link`.
+    pub fn propagate(mut self, propagation: Propagation) -> Pipeline<A, B> {
+        self.propagation = Some(propagation);
+        self
+    }
+
+    /// Spawns the thread that runs `stage`, returning the downstream Anytime along with the
+    /// thread's handle.  If the stage panics the downstream Anytime is finalized with
+    /// `FinalizeReason::ProducerPanicked`.
+    pub fn spawn<S>(self, mut stage: S) -> (Arc<Anytime<B>>, JoinHandle<()>)
+    where
+        S: FnMut(&A, &Refinement<'_, A, B>) + Send + 'static,
+    {
+        let Pipeline { upstream, downstream, restart_if, propagation } = self;
+        let downstream = Arc::new(downstream);
+        if let Some(propagation) = propagation {
+            link(&upstream, &downstream, propagation);
+        }
+        // Waiting on the upstream has to end when the downstream is finalized, too.
+        let waiting_on = Arc::downgrade(&upstream);
+        downstream.on_finalized(move |_| {
+            if let Some(upstream) = waiting_on.upgrade() {
+                upstream.wake_waiters();
+            }
+        });
+        let improver = downstream.improver();
+        let target = Arc::clone(&downstream);
+        shutdown::track(Arc::downgrade(&downstream) as Weak<Anytime<B>>);
+        let running = Running::new();
+        let thread = thread::spawn(move || {
+            let _running = running;
+            contain(&*target, || {
+                let stopped = || target.is_final();
+                let mut seen = 0;
+                while !stopped() {
+                    upstream.wait_for_improvement_or(seen, stopped);
+                    let (version, input) = upstream.peek();
+                    let input = match input {
+                        Some(input) if version > seen && !stopped() => input,
+                        _ if upstream.is_final() || stopped() => break,
+                        _ => continue,
+                    };
+                    seen = version;
+                    pause::wait_while_paused(stopped);
+                    if stopped() {
+                        break;
+                    }
+                    let refinement = Refinement {
+                        improver: &improver,
+                        upstream: &upstream,
+                        input: &input,
+                        input_version: version,
+                        checked_version: Cell::new(version),
+                        restart_if: restart_if.as_deref(),
+                    };
+                    stage(&input, &refinement);
+                }
+            })
+        });
+        (downstream, thread)
+    }
+}
+
+/// What a pipeline stage gets besides its input: the means to publish, and to find out when to
+/// stop.
+pub struct Refinement<'a, A: Clone, B: Clone> {
+    improver: &'a Improver<B>,
+    upstream: &'a Anytime<A>,
+    input: &'a A,
+    input_version: u64,
+    /// The newest upstream version `restart_if` has already turned down.
+    checked_version: Cell<u64>,
+    restart_if: Option<&'a RestartIf<A>>,
+}
+
+impl<'a, A: Clone, B: Clone> Refinement<'a, A, B> {
+    /// The downstream Anytime's improver.
+    pub fn improver(&self) -> &Improver<B> {
+        self.improver
+    }
+
+    /// The upstream version the stage's input came from.
+    pub fn input_version(&self) -> u64 {
+        self.input_version
+    }
+
+    /// Returns false once the stage should return: the downstream Anytime is final, or a newer
+    /// upstream value is worth restarting for.
+    pub fn should_continue(&self) -> bool {
+        self.improver.should_continue() && !self.input_superseded()
+    }
+
+    /// Returns true if the upstream has a newer value that is worth restarting the stage for.
+    pub fn input_superseded(&self) -> bool {
+        let version = self.upstream.version();
+        if version <= self.checked_version.get() {
+            return false;
+        }
+        match self.restart_if {
+            None => true,
+            Some(restart_if) => {
+                let (version, newer) = self.upstream.peek();
+                let superseded = newer.is_some_and(|newer| restart_if(self.input, &newer));
+                if !superseded {
+                    self.checked_version.set(version);
+                }
+                superseded
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Mutex, time::Duration};
+
+    use super::*;
+    use crate::{test_support::within, FinalizeReason};
+
+    /// Spawns a pipeline that doubles its input, linked as `propagation` says.
+    fn doubling(
+        propagation: Propagation,
+    ) -> (Arc<Anytime<u32>>, Arc<Anytime<u32>>, JoinHandle<()>) {
+        let upstream = Arc::new(Anytime::new());
+        let (downstream, thread) = Pipeline::new(&upstream, Anytime::new())
+            .propagate(propagation)
+            .spawn(|input: &u32, refinement| {
+                refinement.improver().update_result(input * 2);
+                while refinement.should_continue() {
+                    thread::sleep(Duration::from_millis(1));
+                }
+            });
+        (upstream, downstream, thread)
+    }
+
+    #[test]
+    fn reading_the_upstream_finalizes_a_linked_downstream() {
+        for propagation in [Propagation::Down, Propagation::Both] {
+            let (upstream, downstream, thread) = doubling(propagation);
+            upstream.update_result(21);
+            let reader = Arc::clone(&upstream);
+            assert_eq!(within(move || reader.get_result()), Some(21));
+            within(move || thread.join()).unwrap();
+            assert!(downstream.is_final());
+            assert_eq!(downstream.finalize_reason(), Some(FinalizeReason::Propagated));
+        }
+    }
+
+    #[test]
+    fn reading_the_downstream_finalizes_a_linked_upstream() {
+        for propagation in [Propagation::Up, Propagation::Both] {
+            let (upstream, downstream, thread) = doubling(propagation);
+            upstream.update_result(21);
+            while downstream.version() == 0 {
+                thread::yield_now();
+            }
+            let reader = Arc::clone(&downstream);
+            assert_eq!(within(move || reader.get_result()), Some(42));
+            within(move || thread.join()).unwrap();
+            assert_eq!(upstream.finalize_reason(), Some(FinalizeReason::Propagated));
+        }
+    }
+
+    /// Waits for `anytime` to reach `version`, failing the test if that takes too long.
+    fn wait_for_version<T: Clone + Send + Sync + 'static>(anytime: &Arc<Anytime<T>>, version: u64) {
+        let anytime = Arc::clone(anytime);
+        within(move || {
+            let mut seen = 0;
+            while seen < version {
+                seen = anytime.wait_for_improvement(seen);
+            }
+        });
+    }
+
+    #[test]
+    fn a_newer_upstream_value_restarts_the_stage() {
+        let upstream = Arc::new(Anytime::new());
+        let inputs = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&inputs);
+        let (downstream, _thread) =
+            Pipeline::new(&upstream, Anytime::new()).spawn(move |input: &u32, refinement| {
+                seen.lock().unwrap().push(*input);
+                refinement.improver().update_result(input * 2);
+                while refinement.should_continue() {
+                    thread::sleep(Duration::from_millis(1));
+                }
+            });
+        upstream.update_result(1);
+        wait_for_version(&downstream, 1);
+        upstream.update_result(5);
+        wait_for_version(&downstream, 2);
+        assert_eq!(downstream.get_result(), Some(10));
+        assert_eq!(*inputs.lock().unwrap(), [1, 5]);
+    }
+
+    #[test]
+    fn restart_if_holds_off_restarts_for_small_improvements() {
+        let upstream = Arc::new(Anytime::new());
+        let inputs = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&inputs);
+        let (downstream, _thread) = Pipeline::new(&upstream, Anytime::new())
+            .restart_if(|current: &u32, newer| newer - current >= 10)
+            .spawn(move |input: &u32, refinement| {
+                seen.lock().unwrap().push(*input);
+                refinement.improver().update_result(input * 2);
+                while refinement.should_continue() {
+                    thread::sleep(Duration::from_millis(1));
+                }
+            });
+        upstream.update_result(1);
+        wait_for_version(&downstream, 1);
+        upstream.update_result(2);
+        upstream.update_result(20);
+        wait_for_version(&downstream, 2);
+        assert_eq!(downstream.get_result(), Some(40));
+        assert_eq!(*inputs.lock().unwrap(), [1, 20]);
+    }
+
+    #[test]
+    fn the_thread_stops_once_the_final_upstream_value_is_refined() {
+        let upstream = Arc::new(Anytime::new());
+        let (downstream, thread) = Pipeline::new(&upstream, Anytime::new())
+            .spawn(|input: &u32, refinement| refinement.improver().update_result(input * 2));
+        upstream.update_result(3);
+        assert_eq!(upstream.get_result(), Some(3));
+        within(move || thread.join()).unwrap();
+        assert!(!downstream.is_final());
+        assert_eq!(downstream.get_result(), Some(6));
+    }
+
+    #[test]
+    fn the_thread_stops_once_the_downstream_is_final() {
+        let upstream: Arc<Anytime<u32>> = Arc::new(Anytime::new());
+        let (downstream, thread) = Pipeline::new(&upstream, Anytime::<u32>::new())
+            .spawn(|input, refinement| refinement.improver().update_result(*input));
+        assert_eq!(downstream.get_result(), None);
+        within(move || thread.join()).unwrap();
+        assert!(!upstream.is_final());
+    }
+
+    #[test]
+    fn a_panicking_stage_finalizes_the_downstream() {
+        let upstream = Arc::new(Anytime::new());
+        let (downstream, thread) =
+            Pipeline::new(&upstream, Anytime::<u32>::new()).spawn(|_: &u32, _| panic!("boom"));
+        upstream.update_result(1);
+        within(move || thread.join()).unwrap();
+        assert_eq!(
+            downstream.finalize_reason(),
+            Some(FinalizeReason::ProducerPanicked("boom".to_string()))
+        );
+    }
+}
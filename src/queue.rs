@@ -0,0 +1,346 @@
+//! Sharing a fixed pool of workers between many Anytimes, most urgent first.
+
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::BinaryHeap,
+    mem,
+    panic::{self, AssertUnwindSafe},
+    sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError, Weak},
+    thread::{self, JoinHandle},
+    time::Instant,
+};
+
+use crate::{
+    pause,
+    scope::finalize_panicked,
+    shutdown::{self, Running, Service},
+    Anytime, AnytimeAlgorithm, Clock, Finalizable, FinalizeReason, Improver, SystemClock,
+};
+
+/// Runs the producers of many Anytimes on a bounded pool of worker threads, handing out compute
+/// one time slice at a time in earliest-deadline-first order.
+///
+/// Each job is an Anytime, its deadline and an `AnytimeAlgorithm`, one step of which makes up a
+/// time slice.  A job leaves the queue when its algorithm says it is done, when its Anytime
+/// becomes final, or when its deadline passes, in which case the Anytime is finalized with
+/// `FinalizeReason::Deadline`.  Deadlines are only checked between slices, so pair the queue with
+/// a `DeadlineScheduler` if results must freeze exactly on time.
+///
+/// Dropping the queue waits for the slices in progress, then finalizes the Anytimes of every job
+/// still waiting (with `FinalizeReason::ScopeExit`).
+pub struct AnytimeQueue {
+    shared: Arc<Shared>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+struct Shared {
+    jobs: Mutex<Jobs>,
+    available: Condvar,
+    clock: Arc<dyn Clock>,
+}
+
+struct Jobs {
+    entries: BinaryHeap<Reverse<Entry>>,
+    next_sequence: u64,
+    stopping: bool,
+}
+
+/// One job waiting for a slice, ordered by deadline and then by when it last ran.
+struct Entry {
+    deadline: Instant,
+    sequence: u64,
+    job: Box<dyn Slice>,
+}
+
+/// A job with its value type erased.
+trait Slice: Send {
+    /// Does one slice of work, returning false once there is nothing more to do.
+    fn run(&mut self) -> bool;
+
+    /// The Anytime the job is improving.
+    fn target(&self) -> &dyn Finalizable;
+}
+
+struct Job<T: Clone, A> {
+    anytime: Arc<Anytime<T>>,
+    improver: Improver<T>,
+    algorithm: A,
+}
+
+impl<T, A> Slice for Job<T, A>
+where
+    T: Clone + Send + Sync,
+    A: AnytimeAlgorithm<T> + Send,
+{
+    fn run(&mut self) -> bool {
+        self.algorithm.step(&self.improver)
+    }
+
+    fn target(&self) -> &dyn Finalizable {
+        &*self.anytime
+    }
+}
+
+impl AnytimeQueue {
+    /// Creates a queue served by `workers` threads (at least one).
+    pub fn new(workers: usize) -> AnytimeQueue {
+        AnytimeQueue::with_clock(workers, Arc::new(SystemClock))
+    }
+
+    /// Creates a queue served by `workers` threads (at least one) that checks deadlines against
+    /// `clock`.
+    pub fn with_clock(workers: usize, clock: Arc<dyn Clock>) -> AnytimeQueue {
+        let shared = Arc::new(Shared {
+            jobs: Mutex::new(Jobs {
+                entries: BinaryHeap::new(),
+                next_sequence: 0,
+                stopping: false,
+            }),
+            available: Condvar::new(),
+            clock,
+        });
+        shutdown::serve(Arc::downgrade(&shared) as Weak<dyn Service>);
+        let workers = (0..workers.max(1))
+            .map(|_| {
+                let worker_shared = Arc::clone(&shared);
+                let running = Running::new();
+                thread::Builder::new()
+                    .name("anytime-queue-worker".into())
+                    .spawn(move || {
+                        let _running = running;
+                        worker_shared.work()
+                    })
+                    .expect("failed to spawn an anytime queue worker")
+            })
+            .collect();
+        AnytimeQueue { shared, workers }
+    }
+
+    /// Adds a job that improves `anytime` by running one step of `algorithm` per time slice until
+    /// `deadline`.
+    pub fn push<T, A>(&self, anytime: &Arc<Anytime<T>>, deadline: Instant, algorithm: A)
+    where
+        T: Clone + Send + Sync + 'static,
+        A: AnytimeAlgorithm<T> + Send + 'static,
+    {
+        shutdown::track(Arc::downgrade(anytime) as Weak<Anytime<T>>);
+        let job = Job { anytime: Arc::clone(anytime), improver: anytime.improver(), algorithm };
+        self.shared.enqueue(&mut self.shared.lock(), deadline, Box::new(job));
+    }
+
+    /// Returns the number of jobs waiting for their next slice (not counting those being run).
+    pub fn pending(&self) -> usize {
+        self.shared.lock().entries.len()
+    }
+}
+
+impl Drop for AnytimeQueue {
+    fn drop(&mut self) {
+        self.shared.lock().stopping = true;
+        self.shared.available.notify_all();
+        pause::wake_waiters();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+        let leftovers = mem::take(&mut self.shared.lock().entries);
+        for Reverse(entry) in leftovers {
+            entry.job.target().finalize(FinalizeReason::ScopeExit);
+        }
+    }
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, Jobs> {
+        self.jobs.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn enqueue(&self, jobs: &mut Jobs, deadline: Instant, job: Box<dyn Slice>) {
+        let sequence = jobs.next_sequence;
+        jobs.next_sequence += 1;
+        jobs.entries.push(Reverse(Entry { deadline, sequence, job }));
+        self.available.notify_one();
+    }
+
+    /// Waits for the most urgent job, or returns None once the queue is stopping.
+    fn next_entry(&self) -> Option<Entry> {
+        let mut jobs = self.lock();
+        loop {
+            if jobs.stopping {
+                return None;
+            }
+            if let Some(Reverse(entry)) = jobs.entries.pop() {
+                return Some(entry);
+            }
+            jobs = self.available.wait(jobs).unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+
+    /// The body of every worker thread.
+    fn work(&self) {
+        while let Some(mut entry) = self.next_entry() {
+            pause::wait_while_paused(|| self.lock().stopping);
+            if self.lock().stopping {
+                // Leave the job for `drop` to finalize with the rest.
+                self.enqueue(&mut self.lock(), entry.deadline, entry.job);
+                return;
+            }
+            if entry.job.target().is_final() {
+                continue;
+            }
+            if entry.deadline <= self.clock.now() {
+                entry.job.target().finalize(FinalizeReason::Deadline);
+                continue;
+            }
+            let more = match panic::catch_unwind(AssertUnwindSafe(|| entry.job.run())) {
+                Ok(more) => more,
+                Err(payload) => {
+                    finalize_panicked(entry.job.target(), payload);
+                    false
+                }
+            };
+            if more && !entry.job.target().is_final() {
+                // A fresh sequence number sends the job behind others with the same deadline.
+                self.enqueue(&mut self.lock(), entry.deadline, entry.job);
+            }
+        }
+    }
+}
+
+impl Service for Shared {
+    fn stop(&self) {
+        self.lock().stopping = true;
+        self.available.notify_all();
+    }
+}
+
+impl PartialEq for Entry {
+    fn eq(&self, other: &Entry) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Entry {}
+
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Entry) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Entry {
+    fn cmp(&self, other: &Entry) -> Ordering {
+        (self.deadline, self.sequence).cmp(&(other.deadline, other.sequence))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{panic::panic_any, sync::mpsc, time::Duration};
+
+    use super::*;
+    use crate::{test_support::within, MockClock};
+
+    #[test]
+    fn jobs_run_earliest_deadline_first() {
+        let queue = AnytimeQueue::new(1);
+        let now = Instant::now();
+        let order = Arc::new(Mutex::new(Vec::new()));
+        // Occupy the only worker until every other job is queued.
+        let (started, has_started) = mpsc::channel();
+        let (release, released) = mpsc::channel::<()>();
+        let blocker = Arc::new(Anytime::<u32>::new());
+        queue.push(&blocker, now + Duration::from_secs(60), move |_: &Improver<u32>| {
+            started.send(()).unwrap();
+            released.recv().unwrap();
+            false
+        });
+        has_started.recv_timeout(Duration::from_secs(5)).unwrap();
+        let anytimes: Vec<_> = vec![30, 10, 20]
+            .into_iter()
+            .map(|secs| {
+                let anytime = Arc::new(Anytime::new());
+                let order = Arc::clone(&order);
+                queue.push(
+                    &anytime,
+                    now + Duration::from_secs(secs),
+                    move |improver: &Improver<u64>| {
+                        order.lock().unwrap().push(secs);
+                        improver.update_result(secs);
+                        false
+                    },
+                );
+                anytime
+            })
+            .collect();
+        release.send(()).unwrap();
+        for anytime in anytimes {
+            within(move || {
+                while anytime.version() == 0 {
+                    thread::yield_now()
+                }
+            });
+        }
+        assert_eq!(*order.lock().unwrap(), [10, 20, 30]);
+    }
+
+    #[test]
+    fn jobs_are_finalized_when_their_deadline_passes() {
+        let clock = Arc::new(MockClock::new());
+        let queue = AnytimeQueue::with_clock(1, Arc::clone(&clock) as Arc<dyn Clock>);
+        let anytime = Arc::new(Anytime::new());
+        queue.push(&anytime, clock.now() + Duration::from_secs(1), |improver: &Improver<u32>| {
+            improver.update_result(1);
+            thread::sleep(Duration::from_millis(1));
+            true
+        });
+        clock.advance(Duration::from_secs(2));
+        let target = Arc::clone(&anytime);
+        within(move || {
+            while !target.is_final() {
+                thread::yield_now()
+            }
+        });
+        assert_eq!(anytime.finalize_reason(), Some(FinalizeReason::Deadline));
+    }
+
+    #[test]
+    fn a_panicking_job_finalizes_its_anytime_and_leaves_the_queue() {
+        let queue = AnytimeQueue::new(1);
+        let deadline = Instant::now() + Duration::from_secs(60);
+        let (panicking, other) = (Arc::new(Anytime::<u32>::new()), Arc::new(Anytime::new()));
+        queue.push(&panicking, deadline, |_: &Improver<u32>| panic_any(String::from("boom")));
+        queue.push(&other, deadline, |improver: &Improver<u32>| {
+            improver.update_result(1);
+            false
+        });
+        let target = Arc::clone(&other);
+        within(move || {
+            while target.version() == 0 {
+                thread::yield_now()
+            }
+        });
+        assert_eq!(
+            panicking.finalize_reason(),
+            Some(FinalizeReason::ProducerPanicked("boom".to_string()))
+        );
+        assert_eq!(queue.pending(), 0);
+    }
+
+    #[test]
+    fn dropping_the_queue_finalizes_waiting_jobs() {
+        let queue = AnytimeQueue::new(1);
+        let now = Instant::now();
+        let (busy, starved) = (Arc::new(Anytime::<u32>::new()), Arc::new(Anytime::<u32>::new()));
+        queue.push(&busy, now + Duration::from_secs(60), |_: &Improver<u32>| {
+            thread::sleep(Duration::from_millis(1));
+            true
+        });
+        // Never gets a slice, since the busy job is always more urgent.
+        queue.push(&starved, now + Duration::from_secs(120), |_: &Improver<u32>| {
+            panic!("the starved job ran")
+        });
+        within(move || drop(queue));
+        assert_eq!(busy.finalize_reason(), Some(FinalizeReason::ScopeExit));
+        assert_eq!(starved.finalize_reason(), Some(FinalizeReason::ScopeExit));
+    }
+}
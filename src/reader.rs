@@ -0,0 +1,247 @@
+//! Handles for the consumers of an Anytime.
+
+use std::{
+    sync::{atomic::Ordering, Arc},
+    task::{Context, Poll, Waker},
+    time::{Duration, Instant},
+};
+
+use crate::{Anytime, AuditEntry, CompletionState, FinalizeReason, NotReady, Subscription, View};
+
+/// The consumer side of an Anytime.  Get one from `Anytime::reader`.
+///
+/// A reader can look at the result but not change it.  Once every reader of an Anytime, and every
+/// view and subscription made from one, has been dropped without any of them having read the
+/// result, nobody is left who wants it, so the Anytime is finalized (with
+/// `FinalizeReason::Abandoned`) and `Improver::should_continue` starts returning false, letting
+/// the producers stop.
+pub struct Reader<T: Clone + Send + Sync> {
+    anytime: Arc<Anytime<T>>,
+    hold: Hold<T>,
+    /// Tells this reader's reads apart from other consumers', for `ReadBarrier::Consumers`.
+    consumer: u64,
+}
+
+/// Counts as one of an Anytime's consumers for as long as it lives, see `Reader`.
+pub(crate) struct Hold<T: Clone> {
+    anytime: Arc<Anytime<T>>,
+}
+
+impl<T: Clone> Hold<T> {
+    fn new(anytime: Arc<Anytime<T>>) -> Hold<T> {
+        anytime.consumer_holds.fetch_add(1, Ordering::Relaxed);
+        Hold { anytime }
+    }
+
+    /// Notes that a consumer has read the result, so the Anytime isn't abandoned.
+    pub(crate) fn note_read(&self) {
+        self.anytime.consumer_read.store(true, Ordering::Relaxed);
+    }
+}
+
+impl<T: Clone> Clone for Hold<T> {
+    fn clone(&self) -> Hold<T> {
+        Hold::new(Arc::clone(&self.anytime))
+    }
+}
+
+impl<T: Clone> Drop for Hold<T> {
+    fn drop(&mut self) {
+        if self.anytime.consumer_holds.fetch_sub(1, Ordering::Relaxed) == 1
+            && !self.anytime.consumer_read.load(Ordering::Relaxed)
+        {
+            self.anytime.finalize_with(FinalizeReason::Abandoned);
+        }
+    }
+}
+
+impl<T: Clone + Send + Sync> Reader<T> {
+    pub(crate) fn new(anytime: Arc<Anytime<T>>) -> Reader<T> {
+        let hold = Hold::new(Arc::clone(&anytime));
+        let consumer = anytime.next_consumer_id.fetch_add(1, Ordering::Relaxed);
+        Reader { anytime, hold, consumer }
+    }
+
+    /// The Anytime, for a read that counts against abandoning it.
+    fn reading(&self) -> &Arc<Anytime<T>> {
+        self.hold.note_read();
+        &self.anytime
+    }
+
+    /// See `Anytime::is_final`.
+    pub fn is_final(&self) -> bool {
+        self.anytime.is_final()
+    }
+
+    /// See `Anytime::is_ready`.
+    pub fn is_ready(&self) -> bool {
+        self.anytime.is_ready()
+    }
+
+    /// See `Anytime::audit_trail`.
+    pub fn audit_trail(&self) -> Option<Vec<AuditEntry>> {
+        self.anytime.audit_trail()
+    }
+
+    /// See `Anytime::finalize_reason`.
+    pub fn finalize_reason(&self) -> Option<FinalizeReason> {
+        self.anytime.finalize_reason()
+    }
+
+    /// See `Anytime::state`.
+    pub fn state(&self) -> CompletionState {
+        self.anytime.state()
+    }
+
+    /// See `Anytime::is_ready_hint`.
+    pub fn is_ready_hint(&self) -> bool {
+        self.anytime.is_ready_hint()
+    }
+
+    /// See `Anytime::version`.
+    pub fn version(&self) -> u64 {
+        self.anytime.version()
+    }
+
+    /// See `Anytime::last_read`.  Reads through any handle count, not just this one's.
+    pub fn last_read(&self) -> Option<T> {
+        self.anytime.last_read()
+    }
+
+    /// See `Anytime::improvement_over_last_read`.
+    pub fn improvement_over_last_read(&self) -> Option<f64> {
+        self.anytime.improvement_over_last_read()
+    }
+
+    /// See `Anytime::estimated_time_to`.
+    pub fn estimated_time_to(&self, score: f64) -> Option<Duration> {
+        self.anytime.estimated_time_to(score)
+    }
+
+    /// See `Anytime::get_result`.
+    pub fn get_result(&self) -> Option<T> {
+        self.reading().read(self.consumer, true)
+    }
+
+    /// See `Anytime::get_result_anyway`.
+    pub fn get_result_anyway(&self) -> Option<T> {
+        self.reading().read(self.consumer, false)
+    }
+
+    /// See `Anytime::try_get_result`.
+    pub fn try_get_result(&self) -> Result<T, NotReady> {
+        self.reading().try_read(self.consumer)
+    }
+
+    /// See `Anytime::get_result_or_else_compute`.
+    pub fn get_result_or_else_compute(&self, fallback: impl FnOnce() -> T) -> Option<T> {
+        self.get_result_or_else_compute_within(Duration::MAX, |_| Some(fallback()))
+    }
+
+    /// See `Anytime::get_result_or_else_compute_within`.
+    pub fn get_result_or_else_compute_within(
+        &self,
+        budget: Duration,
+        fallback: impl FnOnce(&dyn Fn() -> bool) -> Option<T>,
+    ) -> Option<T> {
+        self.reading().read_or_else_compute(self.consumer, budget, fallback)
+    }
+
+    /// See `Anytime::result_by`.
+    pub async fn result_by(&self, deadline: Instant) -> Option<T>
+    where
+        T: 'static,
+    {
+        self.reading().read_by(self.consumer, deadline).await
+    }
+
+    /// See `Anytime::get_result_leased`.
+    pub fn get_result_leased(&self, lease: Duration) -> Option<T> {
+        self.reading().get_result_leased(lease)
+    }
+
+    /// See `Anytime::wait_for_improvement`.
+    pub fn wait_for_improvement(&self, last_seen_version: u64) -> u64 {
+        self.anytime.wait_for_improvement(last_seen_version)
+    }
+
+    /// See `Anytime::register_waker`.
+    pub fn register_waker(&self, waker: &Waker) {
+        self.anytime.register_waker(waker)
+    }
+
+    /// See `Anytime::poll_ready`.
+    pub fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<()> {
+        self.anytime.poll_ready(cx)
+    }
+
+    /// See `Anytime::subscribe`.  The subscription counts as a consumer of its own (see
+    /// `Reader`) for as long as it lives.
+    pub fn subscribe(&self) -> Subscription<T>
+    where
+        T: 'static,
+    {
+        let mut subscription = self.anytime.subscribe();
+        subscription.hold = Some(self.hold.clone());
+        subscription
+    }
+
+    /// See `Anytime::view`.  The view counts as a consumer of its own (see `Reader`) for as long
+    /// as it lives.
+    pub fn view(&self) -> View<T> {
+        let mut view = self.anytime.view();
+        view.hold = Some(self.hold.clone());
+        view
+    }
+}
+
+impl<T: Clone + Send + Sync> Clone for Reader<T> {
+    fn clone(&self) -> Reader<T> {
+        Reader::new(Arc::clone(&self.anytime))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ReadBarrier;
+
+    #[test]
+    fn dropping_every_unread_reader_abandons_the_anytime() {
+        let anytime = Arc::new(Anytime::<u32>::new());
+        let (first, second) = (anytime.reader(), anytime.reader());
+        drop(first);
+        assert!(!anytime.is_final());
+        drop(second);
+        assert_eq!(anytime.finalize_reason(), Some(FinalizeReason::Abandoned));
+    }
+
+    #[test]
+    fn readers_that_have_read_dont_abandon_the_anytime() {
+        let anytime = Arc::new(Anytime::new());
+        anytime.update_result(1);
+        let reader = anytime.reader();
+        assert_eq!(reader.get_result_leased(Duration::from_secs(60)), Some(1));
+        drop(reader);
+        assert!(!anytime.is_final());
+
+        let anytime = Arc::new(Anytime::builder().read_barrier(ReadBarrier::Consumers(2)).build());
+        anytime.update_result(1);
+        let reader = anytime.reader();
+        assert_eq!(reader.get_result(), Some(1));
+        drop(reader);
+        assert!(!anytime.is_final());
+    }
+
+    #[test]
+    fn subscriptions_and_views_keep_a_reader_s_anytime_open() {
+        let anytime = Arc::new(Anytime::<u32>::new());
+        let reader = anytime.reader();
+        let (subscription, view) = (reader.subscribe(), reader.view());
+        drop(reader);
+        drop(subscription);
+        assert!(!anytime.is_final());
+        drop(view);
+        assert_eq!(anytime.finalize_reason(), Some(FinalizeReason::Abandoned));
+    }
+}
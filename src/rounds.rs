@@ -0,0 +1,137 @@
+//! Re-planning loops, where the same question is answered again and again.
+
+use std::{
+    fmt, mem,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
+use crate::{diagnostics::debug, Anytime, Finalizable, FinalizeReason, Improver, StaleEpoch};
+
+/// Identifies one round of an `AnytimeRounds`.  Later rounds have larger epochs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Epoch(u64);
+
+impl fmt::Display for Epoch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "epoch #{}", self.0)
+    }
+}
+
+/// An Anytime that is answered afresh every planning cycle.
+///
+/// Each round is a separate Anytime, stamped with its `Epoch`.  Reads report the epoch they came
+/// from, and producers say which epoch their work is for, so a slow producer still working on an
+/// earlier cycle can't pollute the current one: its updates are rejected with `StaleEpoch`.
+/// Starting a new round finalizes the previous one (with `FinalizeReason::NewRound`).
+pub struct AnytimeRounds<T: Clone> {
+    current: Mutex<(Epoch, Arc<Anytime<T>>)>,
+    make: Box<dyn Fn() -> Anytime<T> + Send + Sync>,
+}
+
+impl<T: Clone + Send + Sync + 'static> AnytimeRounds<T> {
+    /// Starts the first round, with default Anytimes.
+    pub fn new() -> AnytimeRounds<T> {
+        AnytimeRounds::with_factory(Anytime::new)
+    }
+
+    /// Starts the first round, getting the Anytime for every round from `make` (handy for
+    /// configuring them with `Anytime::builder`).
+    pub fn with_factory(make: impl Fn() -> Anytime<T> + Send + Sync + 'static) -> AnytimeRounds<T> {
+        AnytimeRounds { current: Mutex::new((Epoch(0), Arc::new(make()))), make: Box::new(make) }
+    }
+
+    /// The current round's epoch and Anytime.
+    pub fn current(&self) -> (Epoch, Arc<Anytime<T>>) {
+        let current = self.lock();
+        (current.0, Arc::clone(&current.1))
+    }
+
+    /// The current round's epoch.
+    pub fn epoch(&self) -> Epoch {
+        self.current().0
+    }
+
+    /// Finalizes the current round and starts the next, returning its epoch.
+    pub fn next_round(&self) -> Epoch {
+        let fresh = Arc::new((self.make)());
+        let (epoch, previous) = {
+            let mut current = self.lock();
+            let epoch = Epoch(current.0 .0 + 1);
+            let previous = mem::replace(&mut *current, (epoch, fresh));
+            (epoch, previous.1)
+        };
+        previous.finalize(FinalizeReason::NewRound);
+        epoch
+    }
+
+    /// Creates a producer handle for the current round, along with the round's epoch.  Once a
+    /// later round starts the handle's updates are simply refused, since its round is final.
+    pub fn improver(&self) -> (Epoch, Improver<T>) {
+        let (epoch, anytime) = self.current();
+        (epoch, anytime.improver())
+    }
+
+    /// Stores an updated result for round `epoch`, if that's still the current round.
+    pub fn update_result(&self, epoch: Epoch, better_result: T) -> Result<(), StaleEpoch> {
+        let (current, anytime) = self.current();
+        if epoch != current {
+            debug!("Rejected an update for epoch #{} during epoch #{}.", epoch.0, current.0);
+            return Err(StaleEpoch);
+        }
+        anytime.update_result(better_result);
+        Ok(())
+    }
+
+    /// Commits to and returns the best option currently available in the current round, along with
+    /// the round's epoch.
+    pub fn get_result(&self) -> (Epoch, Option<T>) {
+        let (epoch, anytime) = self.current();
+        (epoch, anytime.get_result())
+    }
+}
+
+impl<T: Clone> AnytimeRounds<T> {
+    fn lock(&self) -> MutexGuard<'_, (Epoch, Arc<Anytime<T>>)> {
+        self.current.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<T: Clone + Send + Sync + 'static> Default for AnytimeRounds<T> {
+    fn default() -> AnytimeRounds<T> {
+        AnytimeRounds::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stale_updates_are_turned_away() {
+        let rounds = AnytimeRounds::new();
+        let first = rounds.epoch();
+        assert_eq!(rounds.update_result(first, 1), Ok(()));
+        assert_eq!(rounds.get_result(), (first, Some(1)));
+        let second = rounds.next_round();
+        assert!(second > first);
+        assert_eq!(rounds.update_result(first, 2), Err(StaleEpoch));
+        assert_eq!(rounds.update_result(second, 3), Ok(()));
+        assert_eq!(rounds.get_result(), (second, Some(3)));
+    }
+
+    #[test]
+    fn starting_a_round_finalizes_the_last_one() {
+        let rounds = AnytimeRounds::with_factory(|| {
+            Anytime::builder().quality(|v: &u32| f64::from(*v)).build()
+        });
+        let (first, improver) = rounds.improver();
+        let (_, previous) = rounds.current();
+        improver.update_result(1);
+        rounds.next_round();
+        improver.update_result(2);
+        assert_eq!(previous.finalize_reason(), Some(FinalizeReason::NewRound));
+        assert_eq!((previous.peek(), previous.quality()), ((1, Some(1)), Some(1.0)));
+        assert!(rounds.epoch() > first);
+        assert!(!rounds.current().1.is_ready());
+    }
+}
@@ -0,0 +1,280 @@
+//! A single timer thread that finalizes Anytimes when their deadlines pass.
+
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::BinaryHeap,
+    sync::{Arc, Condvar, Mutex, MutexGuard, OnceLock, PoisonError, Weak},
+    thread::{self, JoinHandle},
+    time::Instant,
+};
+
+use crate::{
+    shutdown::{self, Running, Service},
+    Anytime, Clock, Finalizable, FinalizeReason, SystemClock,
+};
+
+/// Finalizes registered Anytimes once their deadlines pass, using one background thread no matter
+/// how many Anytimes are registered.
+///
+/// Most programs can simply use the `global` scheduler, but separate schedulers can be created if
+/// you want to control the lifetime of the timer thread.  Dropping a scheduler stops its thread
+/// without finalizing anything that is still pending.  `shutdown` stops the thread too (after
+/// finalizing everything registered), and the next registration starts a new one.
+pub struct DeadlineScheduler {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    queue: Mutex<Queue>,
+    wake: Condvar,
+    clock: Arc<dyn Clock>,
+}
+
+struct Queue {
+    entries: BinaryHeap<Reverse<Entry>>,
+    next_sequence: u64,
+    /// Bumped to stop the timer thread, which only runs for the generation it was started in.
+    generation: u64,
+    /// The timer thread, unless it has been stopped.
+    timer: Option<JoinHandle<()>>,
+}
+
+/// One registration, ordered by deadline and then by registration order.
+struct Entry {
+    deadline: Instant,
+    sequence: u64,
+    target: Weak<dyn Finalizable>,
+}
+
+impl DeadlineScheduler {
+    /// Creates a scheduler with its own timer thread.
+    pub fn new() -> DeadlineScheduler {
+        DeadlineScheduler::with_clock(Arc::new(SystemClock))
+    }
+
+    /// Creates a scheduler with its own timer thread that reads the time from `clock`.
+    ///
+    /// The timer thread sleeps in real time, so when driving a scheduler with a `MockClock` call
+    /// `run_due` after advancing the clock rather than waiting for the thread to notice.
+    pub fn with_clock(clock: Arc<dyn Clock>) -> DeadlineScheduler {
+        let shared = Arc::new(Shared {
+            queue: Mutex::new(Queue {
+                entries: BinaryHeap::new(),
+                next_sequence: 0,
+                generation: 0,
+                timer: None,
+            }),
+            wake: Condvar::new(),
+            clock,
+        });
+        shared.start_timer(&mut shared.lock());
+        DeadlineScheduler { shared }
+    }
+
+    /// The scheduler shared by the whole process, started the first time it is asked for.
+    pub fn global() -> &'static DeadlineScheduler {
+        static GLOBAL: OnceLock<DeadlineScheduler> = OnceLock::new();
+        GLOBAL.get_or_init(DeadlineScheduler::new)
+    }
+
+    /// Arranges for `anytime` to be finalized at `deadline` (unless something else finalizes it
+    /// first).  The scheduler only holds a weak reference, so registering doesn't keep an
+    /// otherwise abandoned Anytime alive.
+    pub fn register<T: Clone + Send + Sync + 'static>(
+        &self,
+        anytime: &Arc<Anytime<T>>,
+        deadline: Instant,
+    ) {
+        let target: Weak<dyn Finalizable> = Arc::downgrade(anytime) as Weak<Anytime<T>>;
+        shutdown::track(target.clone());
+        let mut queue = self.shared.lock();
+        if queue.timer.is_none() {
+            self.shared.start_timer(&mut queue);
+        }
+        // Forget Anytimes that are gone or final whenever the heap would otherwise have to grow.
+        if queue.entries.len() == queue.entries.capacity() {
+            queue.entries.retain(|Reverse(e)| e.target.upgrade().is_some_and(|t| !t.is_final()));
+        }
+        let sequence = queue.next_sequence;
+        queue.next_sequence += 1;
+        queue.entries.push(Reverse(Entry { deadline, sequence, target }));
+        self.shared.wake.notify_one();
+    }
+
+    /// Finalizes everything that is due right now on the calling thread, returning how many
+    /// Anytimes were finalized by this call.
+    pub fn run_due(&self) -> usize {
+        let due = self.shared.take_due(&mut self.shared.lock(), self.shared.clock.now());
+        Shared::finalize_all(due)
+    }
+
+    /// Returns the number of registrations that haven't come due yet.
+    pub fn pending(&self) -> usize {
+        self.shared.lock().entries.len()
+    }
+}
+
+impl Default for DeadlineScheduler {
+    fn default() -> DeadlineScheduler {
+        DeadlineScheduler::new()
+    }
+}
+
+impl Drop for DeadlineScheduler {
+    fn drop(&mut self) {
+        let timer = {
+            let mut queue = self.shared.lock();
+            queue.generation += 1;
+            queue.timer.take()
+        };
+        self.shared.wake.notify_all();
+        if let Some(timer) = timer {
+            let _ = timer.join();
+        }
+    }
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, Queue> {
+        self.queue.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Spawns a timer thread for the current generation, for the next shutdown to stop.
+    fn start_timer(self: &Arc<Shared>, queue: &mut Queue) {
+        shutdown::serve(Arc::downgrade(self) as Weak<dyn Service>);
+        let (shared, generation, running) = (Arc::clone(self), queue.generation, Running::new());
+        let timer = thread::Builder::new()
+            .name("anytime-deadlines".into())
+            .spawn(move || {
+                let _running = running;
+                shared.run(generation)
+            })
+            .expect("failed to spawn the deadline scheduler thread");
+        queue.timer = Some(timer);
+    }
+
+    /// Removes every entry whose deadline is at or before `now`.
+    fn take_due(&self, queue: &mut Queue, now: Instant) -> Vec<Entry> {
+        let mut due = Vec::new();
+        while queue.entries.peek().is_some_and(|Reverse(e)| e.deadline <= now) {
+            due.extend(queue.entries.pop().map(|Reverse(e)| e));
+        }
+        due
+    }
+
+    fn finalize_all(due: Vec<Entry>) -> usize {
+        due.into_iter()
+            .filter_map(|e| e.target.upgrade())
+            .filter(|target| target.finalize(FinalizeReason::Deadline))
+            .count()
+    }
+
+    /// The body of the timer thread.
+    fn run(&self, generation: u64) {
+        let mut queue = self.lock();
+        while queue.generation == generation {
+            let now = self.clock.now();
+            let due = self.take_due(&mut queue, now);
+            if !due.is_empty() {
+                drop(queue);
+                Shared::finalize_all(due);
+                queue = self.lock();
+                continue;
+            }
+            let next = queue.entries.peek().map(|Reverse(e)| e.deadline);
+            queue = match next {
+                Some(deadline) => {
+                    let timeout = deadline.saturating_duration_since(now);
+                    self.wake.wait_timeout(queue, timeout).unwrap_or_else(PoisonError::into_inner).0
+                }
+                None => self.wake.wait(queue).unwrap_or_else(PoisonError::into_inner),
+            };
+        }
+    }
+}
+
+impl Service for Shared {
+    fn stop(&self) {
+        let mut queue = self.lock();
+        // The shutdown has already finalized everything that was registered.
+        queue.entries.clear();
+        queue.generation += 1;
+        queue.timer = None;
+        self.wake.notify_all();
+    }
+}
+
+impl PartialEq for Entry {
+    fn eq(&self, other: &Entry) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Entry {}
+
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Entry) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Entry {
+    fn cmp(&self, other: &Entry) -> Ordering {
+        (self.deadline, self.sequence).cmp(&(other.deadline, other.sequence))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{test_support::within, MockClock};
+
+    #[test]
+    fn the_timer_thread_finalizes_registrations_on_its_own() {
+        let scheduler = DeadlineScheduler::new();
+        let anytime = Arc::new(Anytime::<u32>::new());
+        scheduler.register(&anytime, Instant::now() + Duration::from_millis(10));
+        within(move || while !anytime.is_final() {});
+        assert_eq!(scheduler.pending(), 0);
+    }
+
+    #[test]
+    fn registrations_dont_keep_abandoned_anytimes_alive() {
+        let clock = Arc::new(MockClock::new());
+        let scheduler = DeadlineScheduler::with_clock(clock.clone());
+        let abandoned = Arc::new(Anytime::<u32>::new());
+        scheduler.register(&abandoned, clock.now() + Duration::from_secs(1));
+        let weak = Arc::downgrade(&abandoned);
+        drop(abandoned);
+        assert!(weak.upgrade().is_none());
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(scheduler.run_due(), 0);
+    }
+
+    #[test]
+    fn finalizes_in_deadline_order() {
+        let clock = Arc::new(MockClock::new());
+        let scheduler = DeadlineScheduler::with_clock(clock.clone());
+        let (early, late) = (Arc::new(Anytime::<u32>::new()), Arc::new(Anytime::<u32>::new()));
+        scheduler.register(&late, clock.now() + Duration::from_secs(2));
+        scheduler.register(&early, clock.now() + Duration::from_secs(1));
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(scheduler.run_due(), 1);
+        assert!(early.is_final() && !late.is_final());
+        assert_eq!(scheduler.pending(), 1);
+    }
+
+    #[test]
+    fn a_stopped_scheduler_forgets_its_entries_and_restarts_on_demand() {
+        let scheduler = DeadlineScheduler::new();
+        let forgotten = Arc::new(Anytime::<u32>::new());
+        scheduler.register(&forgotten, Instant::now() + Duration::from_secs(3600));
+        scheduler.shared.stop();
+        assert_eq!(scheduler.pending(), 0);
+        let due = Arc::new(Anytime::<u32>::new());
+        scheduler.register(&due, Instant::now());
+        within(move || while !due.is_final() {});
+    }
+}
@@ -0,0 +1,245 @@
+//! Tying producers' lifetimes to the code that wants their results.
+
+use std::{
+    any::Any,
+    panic::{self, AssertUnwindSafe},
+    sync::Arc,
+    thread::{self, JoinHandle, ScopedJoinHandle},
+};
+
+use crate::diagnostics::error;
+
+use crate::{
+    shutdown::{self, Running},
+    Anytime, Finalizable, FinalizeReason, Improver,
+};
+
+/// Runs `f` with a scope in which producers for `anytime` can be spawned on threads that borrow
+/// non-`'static` data.
+///
+/// When `f` returns (or panics) the Anytime is finalized, so producers that watch
+/// `Improver::should_continue` wind down, and then every producer is joined before `scope` itself
+/// returns.  The Anytime is therefore always final once `scope` returns.
+pub fn scope<'env, T, F, R>(anytime: &'env Arc<Anytime<T>>, f: F) -> R
+where
+    T: Clone + Send + Sync,
+    F: for<'scope> FnOnce(&ProducerScope<'scope, 'env, T>) -> R,
+{
+    thread::scope(|threads| {
+        let _finalize = FinalizeOnDrop(&**anytime);
+        f(&ProducerScope { threads, anytime })
+    })
+}
+
+/// Spawns producers for one Anytime inside a `scope`.
+pub struct ProducerScope<'scope, 'env: 'scope, T: Clone> {
+    threads: &'scope thread::Scope<'scope, 'env>,
+    anytime: &'env Arc<Anytime<T>>,
+}
+
+impl<'scope, 'env, T: Clone + Send + Sync> ProducerScope<'scope, 'env, T> {
+    /// Runs `producer` on a new scoped thread, handing it its own `Improver`.  If the producer
+    /// panics the Anytime is finalized with `FinalizeReason::ProducerPanicked`.
+    pub fn spawn<P>(&self, producer: P) -> ScopedJoinHandle<'scope, ()>
+    where
+        P: FnOnce(Improver<T>) + Send + 'scope,
+    {
+        let improver = self.anytime.improver();
+        let anytime = self.anytime;
+        self.threads.spawn(move || contain(&**anytime, || producer(improver)))
+    }
+
+    /// The Anytime the producers are feeding.
+    pub fn anytime(&self) -> &'env Arc<Anytime<T>> {
+        self.anytime
+    }
+}
+
+/// Owns a set of Anytimes and the producer threads feeding them, for structured concurrency.
+///
+/// When the group is dropped (normally or while unwinding from a panic) every Anytime in it is
+/// finalized, which tells the producers to stop, and then every producer thread is joined.  No
+/// producer outlives the group improving results that nobody will read.  Producers are expected to
+/// watch `Improver::should_continue`; one that never does will keep the drop waiting.
+#[derive(Default)]
+pub struct ProducerGroup {
+    members: Vec<Arc<dyn Finalizable>>,
+    producers: Vec<JoinHandle<()>>,
+}
+
+impl ProducerGroup {
+    /// Creates an empty group.
+    pub fn new() -> ProducerGroup {
+        ProducerGroup::default()
+    }
+
+    /// Makes `anytime` part of this group, so that it is finalized when the group ends.
+    pub fn track<T: Clone + Send + Sync + 'static>(&mut self, anytime: &Arc<Anytime<T>>) {
+        let member = Arc::clone(anytime) as Arc<dyn Finalizable>;
+        if !self.members.iter().any(|m| Arc::ptr_eq(m, &member)) {
+            shutdown::track(Arc::downgrade(&member));
+            self.members.push(member);
+        }
+    }
+
+    /// Runs `producer` for `anytime` on a new thread owned by this group, handing it its own
+    /// `Improver`.  The Anytime becomes part of the group.  If the producer panics the Anytime is
+    /// finalized with `FinalizeReason::ProducerPanicked`.
+    pub fn spawn<T, P>(&mut self, anytime: &Arc<Anytime<T>>, producer: P)
+    where
+        T: Clone + Send + Sync + 'static,
+        P: FnOnce(Improver<T>) + Send + 'static,
+    {
+        self.track(anytime);
+        let improver = anytime.improver();
+        let target = Arc::clone(anytime);
+        let running = Running::new();
+        self.producers.push(thread::spawn(move || {
+            let _running = running;
+            contain(&*target, || producer(improver))
+        }));
+    }
+
+    /// Ends the group now rather than when it goes out of scope.
+    pub fn finish(self) {}
+}
+
+impl Drop for ProducerGroup {
+    fn drop(&mut self) {
+        for member in &self.members {
+            member.finalize(FinalizeReason::ScopeExit);
+        }
+        for producer in self.producers.drain(..) {
+            let _ = producer.join();
+        }
+    }
+}
+
+/// Finalizes an Anytime when it goes out of scope, however that happens.
+pub(crate) struct FinalizeOnDrop<'a>(pub(crate) &'a dyn Finalizable);
+
+impl Drop for FinalizeOnDrop<'_> {
+    fn drop(&mut self) {
+        self.0.finalize(FinalizeReason::ScopeExit);
+    }
+}
+
+/// Runs `producer`, finalizing `target` if it panics rather than letting the panic escape.
+pub(crate) fn contain(target: &dyn Finalizable, producer: impl FnOnce()) {
+    if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(producer)) {
+        finalize_panicked(target, payload);
+    }
+}
+
+/// Finalizes `target` on behalf of a producer that panicked with `payload`.
+pub(crate) fn finalize_panicked(target: &dyn Finalizable, payload: Box<dyn Any + Send>) {
+    let message = payload
+        .downcast_ref::<&str>()
+        .map(|m| m.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "<non-string panic payload>".to_string());
+    error!("A producer panicked: {}", message.as_str());
+    target.finalize(FinalizeReason::ProducerPanicked(message));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        panic::panic_any,
+        sync::atomic::{AtomicBool, Ordering},
+    };
+
+    use super::*;
+    use crate::test_support::within;
+
+    /// Runs a group producer that panics with `payload`, returning the Anytime's finalize reason.
+    fn panicked_with(payload: impl Any + Send + 'static) -> Option<FinalizeReason> {
+        let anytime = Arc::new(Anytime::<u32>::new());
+        let mut group = ProducerGroup::new();
+        group.spawn(&anytime, move |_| panic_any(payload));
+        let target = Arc::clone(&anytime);
+        within(move || target.wait_for_improvement(0));
+        group.finish();
+        anytime.finalize_reason()
+    }
+
+    #[test]
+    fn a_panicking_group_producer_finalizes_its_anytime_with_the_message() {
+        let panicked = |message: &str| Some(FinalizeReason::ProducerPanicked(message.to_string()));
+        assert_eq!(panicked_with("static message"), panicked("static message"));
+        assert_eq!(panicked_with(String::from("formatted")), panicked("formatted"));
+        assert_eq!(panicked_with(42), panicked("<non-string panic payload>"));
+    }
+
+    #[test]
+    fn a_panicking_producer_spares_the_rest_of_its_group() {
+        let (panicking, other) = (Arc::new(Anytime::<u32>::new()), Arc::new(Anytime::new()));
+        let mut group = ProducerGroup::new();
+        group.spawn(&panicking, |_| panic!("boom"));
+        group.spawn(&other, |improver| improver.update_result(1));
+        for anytime in [&panicking, &other] {
+            let target = Arc::clone(anytime);
+            within(move || target.wait_for_improvement(0));
+        }
+        group.finish();
+        assert!(matches!(panicking.finalize_reason(), Some(FinalizeReason::ProducerPanicked(_))));
+        assert_eq!(other.finalize_reason(), Some(FinalizeReason::ScopeExit));
+        assert_eq!(other.get_result(), Some(1));
+    }
+
+    #[test]
+    fn ending_a_group_stops_and_joins_its_producers() {
+        let anytime = Arc::new(Anytime::<u32>::new());
+        let stopped = Arc::new(AtomicBool::new(false));
+        let mut group = ProducerGroup::new();
+        let flag = Arc::clone(&stopped);
+        group.spawn(&anytime, move |improver| {
+            while improver.should_continue() {
+                thread::yield_now();
+            }
+            flag.store(true, Ordering::Relaxed);
+        });
+        within(move || group.finish());
+        assert!(stopped.load(Ordering::Relaxed));
+        assert_eq!(anytime.finalize_reason(), Some(FinalizeReason::ScopeExit));
+    }
+
+    #[test]
+    fn scope_finalizes_its_anytime_and_contains_panics() {
+        let anytime = Arc::new(Anytime::<u32>::new());
+        let borrowed = 7;
+        scope(&anytime, |producers| {
+            producers.spawn(|improver| improver.update_result(borrowed));
+        });
+        assert_eq!(anytime.finalize_reason(), Some(FinalizeReason::ScopeExit));
+        let anytime = Arc::new(Anytime::<u32>::new());
+        scope(&anytime, |producers| {
+            producers.spawn(|_| panic!("boom")).join().unwrap();
+        });
+        assert_eq!(
+            anytime.finalize_reason(),
+            Some(FinalizeReason::ProducerPanicked("boom".to_string()))
+        );
+    }
+
+    #[test]
+    fn a_producer_that_poisons_the_lock_still_finalizes_its_anytime() {
+        let anytime = Arc::new(
+            Anytime::builder().ensemble(|_: &[(&u32, f64)]| panic!("combine failed")).build(),
+        );
+        let mut group = ProducerGroup::new();
+        group.spawn(&anytime, |improver| improver.update_result(1));
+        let target = Arc::clone(&anytime);
+        within(move || {
+            while !target.is_final() {
+                thread::yield_now();
+            }
+        });
+        group.finish();
+        assert!(anytime.is_final());
+        assert_eq!(
+            anytime.finalize_reason(),
+            Some(FinalizeReason::ProducerPanicked("combine failed".to_string()))
+        );
+    }
+}
@@ -0,0 +1,102 @@
+//! Tearing down everything the crate runs in the background, for clean exits and isolated tests.
+
+use std::{
+    mem,
+    sync::{Condvar, Mutex, MutexGuard, PoisonError, Weak},
+    time::{Duration, Instant},
+};
+
+use crate::{pause, Finalizable, FinalizeReason};
+
+/// What the crate's background machinery has registered since the last shutdown.
+struct Registry {
+    /// Anytimes handed to a scheduler, queue, pipeline, producer group or runner.
+    anytimes: Vec<Weak<dyn Finalizable>>,
+    /// Machinery with threads of its own that have to be told to stop.
+    services: Vec<Weak<dyn Service>>,
+    /// How many of the crate's threads haven't finished yet.
+    running: usize,
+}
+
+static REGISTRY: Mutex<Registry> =
+    Mutex::new(Registry { anytimes: Vec::new(), services: Vec::new(), running: 0 });
+static FINISHED: Condvar = Condvar::new();
+
+/// Background machinery whose threads only stop when told to.
+pub(crate) trait Service: Send + Sync {
+    /// Tells the threads to stop, without waiting for them.
+    fn stop(&self);
+}
+
+/// Finalizes every Anytime that has been handed to the crate's background machinery (deadline
+/// schedulers, queues, pipelines, producer groups and the tokio runner) with
+/// `FinalizeReason::Shutdown`, which tells their producers to stop, stops every `AnytimeQueue`
+/// and deadline scheduler timer, and then waits up to `timeout` for the threads the crate spawned
+/// to finish.
+///
+/// Returns true if every thread finished in time.  Producers that never check
+/// `Improver::should_continue` keep their threads running past the timeout.  Queues that were
+/// stopped don't run jobs pushed to them afterwards, but anything created after the shutdown
+/// works as usual and is covered by the next one, so tests can shut down between cases.
+pub fn shutdown(timeout: Duration) -> bool {
+    let started = Instant::now();
+    let (anytimes, services) = {
+        let mut registry = lock();
+        (mem::take(&mut registry.anytimes), mem::take(&mut registry.services))
+    };
+    for anytime in anytimes.iter().filter_map(Weak::upgrade) {
+        anytime.finalize(FinalizeReason::Shutdown);
+    }
+    services.iter().filter_map(Weak::upgrade).for_each(|service| service.stop());
+    pause::wake_waiters();
+    let mut registry = lock();
+    while registry.running > 0 {
+        let left = match timeout.checked_sub(started.elapsed()) {
+            Some(left) if !left.is_zero() => left,
+            _ => return false,
+        };
+        registry = FINISHED.wait_timeout(registry, left).unwrap_or_else(PoisonError::into_inner).0;
+    }
+    true
+}
+
+/// Registers an Anytime for the next shutdown to finalize.
+pub(crate) fn track(anytime: Weak<dyn Finalizable>) {
+    let mut registry = lock();
+    // Forget Anytimes that are gone whenever the list would otherwise have to grow.
+    if registry.anytimes.len() == registry.anytimes.capacity() {
+        registry.anytimes.retain(|a| a.strong_count() > 0);
+    }
+    registry.anytimes.push(anytime);
+}
+
+/// Registers machinery for the next shutdown to stop.
+pub(crate) fn serve(service: Weak<dyn Service>) {
+    let mut registry = lock();
+    if registry.services.len() == registry.services.capacity() {
+        registry.services.retain(|s| s.strong_count() > 0);
+    }
+    registry.services.push(service);
+}
+
+/// Counts one of the crate's threads as running until dropped.  Create it before spawning the
+/// thread and move it in, so that a shutdown can't miss a thread that hasn't started yet.
+pub(crate) struct Running(());
+
+impl Running {
+    pub(crate) fn new() -> Running {
+        lock().running += 1;
+        Running(())
+    }
+}
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        lock().running -= 1;
+        FINISHED.notify_all();
+    }
+}
+
+fn lock() -> MutexGuard<'static, Registry> {
+    REGISTRY.lock().unwrap_or_else(PoisonError::into_inner)
+}
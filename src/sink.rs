@@ -0,0 +1,198 @@
+//! Plugging Anytimes into async pipelines.  Only available with the `async` feature.
+
+use std::{
+    future::poll_fn,
+    pin::{pin, Pin},
+    task::{Context, Poll},
+};
+
+use futures_core::Stream;
+use futures_sink::Sink;
+
+use crate::{Anytime, Finalized, Improver, Subscription};
+
+impl<T: Clone> Anytime<T> {
+    /// Stores every item of `stream` as an update until the stream ends or this Anytime becomes
+    /// final, whichever comes first.
+    pub async fn feed_from<S: Stream<Item = T>>(&self, stream: S) {
+        let mut stream = pin!(stream);
+        while !self.is_final() {
+            match poll_fn(|cx| stream.as_mut().poll_next(cx)).await {
+                Some(candidate) => self.update_result(candidate),
+                None => break,
+            }
+        }
+    }
+}
+
+impl<T: Clone> Improver<T> {
+    /// Stores every item of `stream` as an update from this producer until the stream ends or the
+    /// Anytime becomes final, whichever comes first.
+    pub async fn feed_from<S: Stream<Item = T>>(&self, stream: S) {
+        let mut stream = pin!(stream);
+        while self.should_continue() {
+            match poll_fn(|cx| stream.as_mut().poll_next(cx)).await {
+                Some(candidate) => self.update_result(candidate),
+                None => break,
+            }
+        }
+    }
+}
+
+/// An improver is a sink for candidates.  Sending never waits, and fails with `Finalized` once the
+/// Anytime is final so that forwarding a stream into it stops.
+impl<T: Clone> Sink<T> for Improver<T> {
+    type Error = Finalized;
+
+    fn poll_ready(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Finalized>> {
+        Poll::Ready(if self.should_continue() { Ok(()) } else { Err(Finalized) })
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: T) -> Result<(), Finalized> {
+        if self.should_continue() {
+            self.update_result(item);
+            Ok(())
+        } else {
+            Err(Finalized)
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Finalized>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Finalized>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<T: Clone + Send + 'static> Subscription<T> {
+    /// Turns this subscription into a `Stream` of the same improvements.
+    pub fn into_stream(self) -> SubscriptionStream<T> {
+        SubscriptionStream { subscription: self }
+    }
+}
+
+/// A subscription as a stream of improvements, ending once the Anytime is final and everything it
+/// accepted has been seen.  Get one from `Subscription::into_stream`.
+pub struct SubscriptionStream<T: Clone + Send + 'static> {
+    subscription: Subscription<T>,
+}
+
+impl<T: Clone + Send + 'static> Stream for SubscriptionStream<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let subscription = &mut self.get_mut().subscription;
+        for registered in [false, true] {
+            let finished = subscription.anytime.is_final();
+            if let Some(value) = subscription.try_next() {
+                return Poll::Ready(Some(value));
+            }
+            if finished {
+                return Poll::Ready(None);
+            }
+            if !registered {
+                subscription.anytime.register_waker(cx.waker());
+            }
+        }
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        future::Future,
+        sync::Arc,
+        task::{Wake, Waker},
+        thread::{self, Thread},
+    };
+
+    use super::*;
+    use crate::test_support::within;
+
+    /// Unparks the thread blocked on a future.
+    struct Unpark(Thread);
+
+    impl Wake for Unpark {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    /// Polls `future` on this thread until it is done.
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let waker = Waker::from(Arc::new(Unpark(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        let mut future = pin!(future);
+        loop {
+            match future.as_mut().poll(&mut cx) {
+                Poll::Ready(output) => return output,
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+
+    /// A stream of the given items that counts how many it has handed out.
+    struct Items {
+        items: Vec<u32>,
+        taken: usize,
+    }
+
+    impl Stream for Items {
+        type Item = u32;
+
+        fn poll_next(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<u32>> {
+            let this = self.get_mut();
+            let item = this.items.get(this.taken).copied();
+            this.taken += usize::from(item.is_some());
+            Poll::Ready(item)
+        }
+    }
+
+    #[test]
+    fn feeding_stores_every_item_until_the_anytime_is_final() {
+        let anytime = Arc::new(Anytime::new());
+        block_on(anytime.feed_from(Items { items: vec![1, 2, 3], taken: 0 }));
+        assert_eq!(anytime.peek(), (3, Some(3)));
+        assert_eq!(anytime.get_result(), Some(3));
+        let mut stream = Items { items: vec![4], taken: 0 };
+        block_on(anytime.improver().feed_from(&mut stream));
+        assert_eq!(stream.taken, 0);
+    }
+
+    #[test]
+    fn improvers_refuse_sends_once_the_anytime_is_final() {
+        let anytime = Arc::new(Anytime::new());
+        let mut improver = anytime.improver();
+        let waker = Waker::from(Arc::new(Unpark(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        assert_eq!(Pin::new(&mut improver).poll_ready(&mut cx), Poll::Ready(Ok(())));
+        assert_eq!(Pin::new(&mut improver).start_send(1), Ok(()));
+        assert_eq!(anytime.get_result(), Some(1));
+        assert_eq!(Pin::new(&mut improver).poll_ready(&mut cx), Poll::Ready(Err(Finalized)));
+        assert_eq!(Pin::new(&mut improver).start_send(2), Err(Finalized));
+    }
+
+    #[test]
+    fn subscription_streams_end_once_everything_has_been_seen() {
+        let anytime = Arc::new(Anytime::new());
+        anytime.update_result(1);
+        let mut stream = anytime.subscribe().into_stream();
+        let producer = Arc::clone(&anytime);
+        let read = thread::spawn(move || {
+            producer.update_result(2);
+            producer.get_result()
+        });
+        let seen = within(move || {
+            let mut seen = Vec::new();
+            while let Some(value) = block_on(poll_fn(|cx| Pin::new(&mut stream).poll_next(cx))) {
+                seen.push(value);
+            }
+            seen
+        });
+        assert_eq!(read.join().unwrap(), Some(2));
+        assert_eq!(seen, vec![1, 2]);
+    }
+}
@@ -0,0 +1,82 @@
+//! Counters describing how an Anytime has been used.
+
+use std::time::{Duration, Instant};
+
+/// A snapshot of an Anytime's counters and timings, see `Anytime::stats`.  These are cheap enough
+/// to keep for every job, unlike a full history of the values.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AnytimeStats {
+    /// How many updates were stored.
+    pub accepted_updates: u64,
+    /// How many updates were thrown away, usually because they arrived after finalization.  Each
+    /// one is producer work that was wasted.
+    pub rejected_updates: u64,
+    /// When the first update was stored, according to the Anytime's clock.
+    pub first_accepted_at: Option<Instant>,
+    /// When the most recent update was stored, according to the Anytime's clock.
+    pub last_accepted_at: Option<Instant>,
+    /// How long after its creation the Anytime became final, if it has.
+    pub time_to_finalization: Option<Duration>,
+    /// How many different producers have submitted updates, accepted or not.  Every update made
+    /// directly through the Anytime counts as coming from the same (anonymous) producer.
+    pub distinct_producers: usize,
+    /// How many of the rejected updates were turned away by the validator (see
+    /// `AnytimeBuilder::validator`).
+    pub invalid_updates: u64,
+    /// How many updates were skipped for being the same as the current value (see
+    /// `AnytimeBuilder::skip_duplicates`).  These are neither accepted nor rejected.
+    pub duplicate_updates: u64,
+    /// How many times the value was rolled back to the one before it (see
+    /// `Anytime::revert_to_previous`).  Each revert makes a new version, like an accepted update.
+    pub reverts: u64,
+    /// How many times reading or updating the Anytime had to wait for another thread to let go of
+    /// it.  If this is a large share of all accesses, the Anytime is contended: consider batching
+    /// updates (`update_many`) or publishing less often.
+    pub contended_locks: u64,
+    /// Whether the Anytime has been contended enough that reads which change nothing (`version`,
+    /// `is_ready`, views and pipelines) have switched from its lock to a snapshot of the current
+    /// value.  The switch is made automatically, and is never undone.
+    pub snapshot_reads: bool,
+    /// How long reads and updates waited for the lock and held it.
+    #[cfg(feature = "lock-metrics")]
+    pub lock_metrics: crate::LockMetrics,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{Anytime, Clock, MockClock};
+
+    #[test]
+    fn stats_count_accepted_and_rejected_updates() {
+        let anytime = Anytime::new();
+        anytime.update_result(1);
+        anytime.update_result(2);
+        assert_eq!(anytime.get_result(), Some(2));
+        anytime.update_result(3);
+        let stats = anytime.stats();
+        assert_eq!((stats.accepted_updates, stats.rejected_updates), (2, 1));
+    }
+
+    #[test]
+    fn stats_time_updates_with_the_anytimes_clock_and_count_producers() {
+        let clock = Arc::new(MockClock::new());
+        let anytime = Arc::new(Anytime::builder().clock(clock.clone()).build());
+        let created = clock.now();
+        assert_eq!(anytime.stats().first_accepted_at, None);
+        anytime.update_result(1);
+        clock.advance(Duration::from_secs(1));
+        anytime.improver().update_result(2);
+        anytime.improver().update_result(3);
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(anytime.get_result(), Some(3));
+        anytime.update_result(4);
+        let stats = anytime.stats();
+        assert_eq!(stats.first_accepted_at, Some(created));
+        assert_eq!(stats.last_accepted_at, Some(created + Duration::from_secs(1)));
+        assert_eq!(stats.time_to_finalization, Some(Duration::from_secs(2)));
+        assert_eq!(stats.distinct_producers, 3);
+    }
+}
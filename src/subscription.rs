@@ -0,0 +1,150 @@
+//! Live views of the sequence of improvements, one cursor per consumer.
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use crate::{reader::Hold, Anytime, ObserverToken};
+
+/// Follows every improvement an Anytime accepts, in order, without freezing it.  Get one from
+/// `Anytime::subscribe`.
+///
+/// Each subscription keeps its own queue of the values it hasn't handed out yet, so any number of
+/// consumers can follow the same Anytime at their own pace without missing or re-seeing values.
+/// The queue is unbounded: a subscription that is never read holds on to every improvement.
+/// Values are queued by reference to the ones the Anytime stores, and only cloned when handed
+/// out.
+///
+/// Iterating blocks until the next improvement arrives and ends once the Anytime is final and
+/// every value accepted before that has been seen.  With the `async` feature a subscription can
+/// also be turned into a `Stream`, see `into_stream`.
+pub struct Subscription<T: Clone + Send + 'static> {
+    pub(crate) anytime: Arc<Anytime<T>>,
+    feed: Arc<Feed<T>>,
+    token: ObserverToken,
+    last_seen_version: u64,
+    /// Keeps the Anytime from being abandoned, for subscriptions made from a `Reader`.
+    pub(crate) hold: Option<Hold<T>>,
+}
+
+/// The queue an Anytime fills for one subscription.
+pub(crate) struct Feed<T> {
+    queue: Mutex<VecDeque<(u64, Arc<T>)>>,
+}
+
+impl<T> Feed<T> {
+    pub(crate) fn new(first: Option<(u64, Arc<T>)>) -> Feed<T> {
+        Feed { queue: Mutex::new(first.into_iter().collect()) }
+    }
+
+    /// Queues an accepted value.  Called with the Anytime locked, so it only shares the value.
+    pub(crate) fn push(&self, version: u64, value: &Arc<T>) {
+        self.queue
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push_back((version, Arc::clone(value)));
+    }
+}
+
+impl<T: Clone + Send + 'static> Subscription<T> {
+    pub(crate) fn new(
+        anytime: Arc<Anytime<T>>,
+        feed: Arc<Feed<T>>,
+        token: ObserverToken,
+        last_seen_version: u64,
+    ) -> Subscription<T> {
+        Subscription { anytime, feed, token, last_seen_version, hold: None }
+    }
+
+    /// The version of the last value this subscription handed out (or of the Anytime when the
+    /// subscription started, if it hasn't handed out any).
+    pub fn last_seen_version(&self) -> u64 {
+        self.last_seen_version
+    }
+
+    /// Returns the next improvement if one has already arrived, without waiting.
+    pub fn try_next(&mut self) -> Option<T> {
+        let (version, value) =
+            self.feed.queue.lock().unwrap_or_else(PoisonError::into_inner).pop_front()?;
+        self.last_seen_version = version;
+        if let Some(hold) = &self.hold {
+            hold.note_read();
+        }
+        Some(Arc::try_unwrap(value).unwrap_or_else(|shared| T::clone(&shared)))
+    }
+
+    /// Returns true once nothing more will ever arrive: the Anytime is final and everything it
+    /// accepted has been handed out.
+    pub fn is_exhausted(&self) -> bool {
+        // Check finality first, every value accepted before finalization is already queued by then.
+        self.anytime.is_final()
+            && self.feed.queue.lock().unwrap_or_else(PoisonError::into_inner).is_empty()
+    }
+}
+
+impl<T: Clone + Send + 'static> Iterator for Subscription<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        loop {
+            let finished = self.anytime.is_final();
+            if let Some(value) = self.try_next() {
+                return Some(value);
+            }
+            if finished {
+                return None;
+            }
+            self.anytime.wait_for_improvement(self.last_seen_version);
+        }
+    }
+}
+
+impl<T: Clone + Send + 'static> Drop for Subscription<T> {
+    fn drop(&mut self) {
+        self.anytime.unsubscribe(self.token);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    /// Counts how many times it has been cloned.
+    struct Counted(Arc<AtomicUsize>);
+
+    impl Clone for Counted {
+        fn clone(&self) -> Counted {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            Counted(Arc::clone(&self.0))
+        }
+    }
+
+    #[test]
+    fn updates_are_only_cloned_when_a_subscription_hands_them_out() {
+        let clones = Arc::new(AtomicUsize::new(0));
+        let anytime = Arc::new(Anytime::new());
+        let mut subscriptions: Vec<_> = (0..3).map(|_| anytime.subscribe()).collect();
+        anytime.update_result(Counted(Arc::clone(&clones)));
+        assert_eq!(clones.load(Ordering::Relaxed), 0);
+        for subscription in &mut subscriptions {
+            assert!(subscription.try_next().is_some());
+            assert_eq!(subscription.last_seen_version(), 1);
+        }
+        assert_eq!(clones.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn subscriptions_see_every_improvement_then_end() {
+        let anytime = Arc::new(Anytime::new());
+        anytime.update_result(1);
+        let subscription = anytime.subscribe();
+        anytime.update_result(2);
+        anytime.update_result(3);
+        assert_eq!(anytime.get_result(), Some(3));
+        anytime.update_result(4);
+        assert_eq!(subscription.collect::<Vec<_>>(), [1, 2, 3]);
+    }
+}
@@ -0,0 +1,14 @@
+//! Helpers shared by the unit tests.
+
+use std::{sync::mpsc, thread, time::Duration};
+
+/// How long a test waits for something that should happen promptly before calling it a hang.
+pub(crate) const PATIENCE: Duration = Duration::from_secs(5);
+
+/// Runs `f` on another thread and returns its result, failing the test if it takes longer than
+/// `PATIENCE` (a deadlock, say) instead of hanging the whole test run.
+pub(crate) fn within<R: Send + 'static>(f: impl FnOnce() -> R + Send + 'static) -> R {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || sender.send(f()));
+    receiver.recv_timeout(PATIENCE).expect("timed out, probably deadlocked")
+}
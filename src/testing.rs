@@ -0,0 +1,144 @@
+//! Helpers for testing code that consumes Anytimes.  Only available with the `testing` feature.
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex, PoisonError},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use crate::{Anytime, Clock, SystemClock};
+
+/// An Anytime whose improvements follow a predetermined script, so that consumer logic (polling
+/// policies, timeout handling and the like) can be tested deterministically.
+///
+/// Each step of the script is a value and how long after the start of the script it should be
+/// published.  Steps are published lazily: every read through the `ScriptedAnytime` first
+/// publishes whatever has come due according to its clock.  With a `MockClock` that means advancing
+/// the clock is all it takes to move the script along; with the system clock `play` can be used to
+/// publish each step on time from a background thread instead.
+pub struct ScriptedAnytime<T: Clone> {
+    anytime: Arc<Anytime<T>>,
+    clock: Arc<dyn Clock>,
+    start: Instant,
+    script: Mutex<VecDeque<(Duration, T)>>,
+}
+
+impl<T: Clone> ScriptedAnytime<T> {
+    /// Creates a scripted Anytime that follows the system clock, starting now.
+    pub fn new(script: impl IntoIterator<Item = (Duration, T)>) -> ScriptedAnytime<T> {
+        ScriptedAnytime::with_clock(script, Arc::new(SystemClock))
+    }
+
+    /// Creates a scripted Anytime that follows `clock`, starting at the clock's current time.
+    pub fn with_clock(
+        script: impl IntoIterator<Item = (Duration, T)>,
+        clock: Arc<dyn Clock>,
+    ) -> ScriptedAnytime<T> {
+        let mut steps: Vec<_> = script.into_iter().collect();
+        steps.sort_by_key(|(offset, _)| *offset);
+        ScriptedAnytime {
+            anytime: Arc::new(Anytime::builder().clock(Arc::clone(&clock)).build()),
+            start: clock.now(),
+            clock,
+            script: Mutex::new(steps.into()),
+        }
+    }
+
+    /// The Anytime being scripted.  Reading it directly does not move the script along.
+    pub fn anytime(&self) -> &Arc<Anytime<T>> {
+        &self.anytime
+    }
+
+    /// Publishes every step that has come due, returning how many were published.
+    pub fn pump(&self) -> usize {
+        let elapsed = self.clock.now().saturating_duration_since(self.start);
+        let mut script = self.script.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut published = 0;
+        while script.front().is_some_and(|(offset, _)| *offset <= elapsed) {
+            if let Some((_, value)) = script.pop_front() {
+                self.anytime.update_result(value);
+                published += 1;
+            }
+        }
+        published
+    }
+
+    /// Returns true once every step of the script has been published.
+    pub fn is_exhausted(&self) -> bool {
+        self.script.lock().unwrap_or_else(PoisonError::into_inner).is_empty()
+    }
+
+    /// How long after the start of the script the next unpublished step is due.
+    pub fn next_step_due(&self) -> Option<Duration> {
+        self.script.lock().unwrap_or_else(PoisonError::into_inner).front().map(|(o, _)| *o)
+    }
+
+    /// Publishes due steps, then behaves like `Anytime::is_final`.
+    pub fn is_final(&self) -> bool {
+        self.pump();
+        self.anytime.is_final()
+    }
+
+    /// Publishes due steps, then behaves like `Anytime::is_ready`.
+    pub fn is_ready(&self) -> bool {
+        self.pump();
+        self.anytime.is_ready()
+    }
+
+    /// Publishes due steps, then behaves like `Anytime::get_result`.
+    pub fn get_result(&self) -> Option<T> {
+        self.pump();
+        self.anytime.get_result()
+    }
+}
+
+impl<T: Clone + Send + Sync + 'static> ScriptedAnytime<T> {
+    /// Spawns a thread that publishes each step when it comes due, until the script is exhausted
+    /// or the Anytime is finalized.  The thread sleeps in real time, so this is only meaningful
+    /// with the system clock.
+    pub fn play(self: &Arc<Self>) -> JoinHandle<()> {
+        let scripted = Arc::clone(self);
+        thread::spawn(move || {
+            while !scripted.is_final() {
+                let due = match scripted.next_step_due() {
+                    Some(due) => due,
+                    None => break,
+                };
+                let elapsed = scripted.clock.now().saturating_duration_since(scripted.start);
+                thread::sleep(due.saturating_sub(elapsed));
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_support::within, MockClock};
+
+    #[test]
+    fn mock_clocks_move_the_script_along() {
+        let clock = Arc::new(MockClock::new());
+        let script =
+            [(Duration::from_secs(2), 2), (Duration::ZERO, 1), (Duration::from_secs(5), 3)];
+        let scripted = ScriptedAnytime::with_clock(script, clock.clone());
+        assert!(scripted.is_ready());
+        assert_eq!(scripted.anytime().version(), 1);
+        assert_eq!(scripted.next_step_due(), Some(Duration::from_secs(2)));
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(scripted.pump(), 2);
+        assert!(scripted.is_exhausted());
+        assert_eq!(scripted.get_result(), Some(3));
+    }
+
+    #[test]
+    fn playing_publishes_each_step_on_time() {
+        let script = [(Duration::ZERO, 1), (Duration::from_millis(10), 2)];
+        let scripted = Arc::new(ScriptedAnytime::new(script));
+        let player = scripted.play();
+        within(move || player.join().unwrap());
+        assert!(scripted.is_exhausted());
+        assert_eq!(scripted.anytime().peek(), (2, Some(2)));
+    }
+}
@@ -0,0 +1,110 @@
+//! Running async producers on tokio.  Only available with the `tokio` feature.
+
+use std::{
+    future::{poll_fn, Future},
+    panic::{self, AssertUnwindSafe},
+    pin::pin,
+    sync::{Arc, Weak},
+    task::Poll,
+};
+
+use crate::{scope::finalize_panicked, shutdown, Anytime, CancellationSignal, Improver, Reader};
+
+/// Spawns an async producer for `anytime` on the current tokio runtime.
+///
+/// `producer` is given an `Improver` to publish with and a `CancellationSignal` that trips when the
+/// Anytime becomes final.  The task also stops by itself at that point, even if the producer never
+/// looks at the signal, and it can't outlive the runtime.  Returns a `Reader` for consumers along
+/// with the task's handle; dropping every reader without reading cancels the task.  If the
+/// producer panics the task ends and the Anytime is finalized with
+/// `FinalizeReason::ProducerPanicked`.
+///
+/// # Panics
+///
+/// Panics if called outside of a tokio runtime.
+pub fn spawn_tokio<T, F, Fut>(
+    anytime: Anytime<T>,
+    producer: F,
+) -> (Reader<T>, tokio::task::JoinHandle<()>)
+where
+    T: Clone + Send + Sync + 'static,
+    F: FnOnce(Improver<T>, CancellationSignal) -> Fut,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let anytime = Arc::new(anytime);
+    shutdown::track(Arc::downgrade(&anytime) as Weak<Anytime<T>>);
+    let signal = CancellationSignal::new(&anytime);
+    let work = producer(anytime.improver(), signal.clone());
+    let target = Arc::clone(&anytime);
+    let task = tokio::spawn(async move {
+        let mut work = pin!(work);
+        let mut cancelled = pin!(signal.cancelled());
+        poll_fn(|cx| {
+            if cancelled.as_mut().poll(cx).is_ready() {
+                return Poll::Ready(());
+            }
+            panic::catch_unwind(AssertUnwindSafe(|| work.as_mut().poll(cx))).unwrap_or_else(
+                |payload| {
+                    finalize_panicked(&*target, payload);
+                    Poll::Ready(())
+                },
+            )
+        })
+        .await
+    });
+    (anytime.reader(), task)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::pending;
+
+    use tokio::runtime::{Builder, Runtime};
+
+    use super::*;
+    use crate::FinalizeReason;
+
+    fn runtime() -> Runtime {
+        Builder::new_current_thread().build().unwrap()
+    }
+
+    #[test]
+    fn the_task_stops_once_the_result_is_read() {
+        runtime().block_on(async {
+            let (reader, task) = spawn_tokio(Anytime::new(), |improver, _| async move {
+                improver.update_result(1);
+                pending::<()>().await
+            });
+            poll_fn(|cx| reader.poll_ready(cx)).await;
+            assert_eq!(reader.get_result(), Some(1));
+            task.await.unwrap();
+        });
+    }
+
+    #[test]
+    fn dropping_every_unread_reader_cancels_the_task() {
+        runtime().block_on(async {
+            let (reader, task) =
+                spawn_tokio(
+                    Anytime::<u32>::new(),
+                    |_, signal| async move { signal.cancelled().await },
+                );
+            drop(reader);
+            task.await.unwrap();
+        });
+    }
+
+    #[test]
+    fn a_panicking_producer_finalizes_its_anytime() {
+        runtime().block_on(async {
+            let (reader, task) = spawn_tokio(Anytime::<u32>::new(), |_, _| async {
+                panic!("no luck");
+            });
+            task.await.unwrap();
+            assert_eq!(
+                reader.finalize_reason(),
+                Some(FinalizeReason::ProducerPanicked("no luck".into()))
+            );
+        });
+    }
+}
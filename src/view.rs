@@ -0,0 +1,103 @@
+//! Consumers that freeze only what they themselves see.
+
+use std::sync::{Arc, OnceLock};
+
+use crate::{reader::Hold, Anytime, NotReady};
+
+/// One consumer's view of an Anytime.  Get one from `Anytime::view`.
+///
+/// The first read through a view pins the value (and version) it returned, and every later read
+/// through the same view returns that pinned value, but nothing else is frozen: the Anytime keeps
+/// improving, and other views pin whatever is current when they are first read.  This gives each
+/// consumer consistency from its first read onward without ending the search for the rest.
+pub struct View<T: Clone> {
+    anytime: Arc<Anytime<T>>,
+    pinned: OnceLock<(u64, Option<T>)>,
+    /// Keeps the Anytime from being abandoned, for views made from a `Reader`.
+    pub(crate) hold: Option<Hold<T>>,
+}
+
+impl<T: Clone> View<T> {
+    pub(crate) fn new(anytime: Arc<Anytime<T>>) -> View<T> {
+        View { anytime, pinned: OnceLock::new(), hold: None }
+    }
+
+    /// Returns true iff this view has been read, and so can no longer change.
+    pub fn is_pinned(&self) -> bool {
+        self.pinned.get().is_some()
+    }
+
+    /// Returns true if this view has been read, or if the Anytime behind it is ready.
+    pub fn is_ready(&self) -> bool {
+        self.is_pinned() || self.anytime.is_ready()
+    }
+
+    /// The version of the Anytime this view was pinned at, if it has been read.
+    pub fn pinned_version(&self) -> Option<u64> {
+        self.pinned.get().map(|(version, _)| *version)
+    }
+
+    /// Pins this view to the best option currently available (unless it was already pinned) and
+    /// returns the pinned value.
+    pub fn get_result(&self) -> Option<T> {
+        self.note_read();
+        self.pinned.get_or_init(|| self.anytime.peek()).1.clone()
+    }
+
+    /// Like `get_result`, except that if there's nothing to return it returns `Err(NotReady)`
+    /// rather than pinning this view to nothing.
+    pub fn try_get_result(&self) -> Result<T, NotReady> {
+        self.note_read();
+        if let Some((_, pinned)) = self.pinned.get() {
+            return pinned.clone().ok_or(NotReady);
+        }
+        match self.anytime.peek() {
+            (version, Some(value)) => {
+                self.pinned.get_or_init(|| (version, Some(value))).1.clone().ok_or(NotReady)
+            }
+            (_, None) => Err(NotReady),
+        }
+    }
+
+    /// Notes a read against abandoning the Anytime, for views made from a `Reader`.
+    fn note_read(&self) {
+        if let Some(hold) = &self.hold {
+            hold.note_read();
+        }
+    }
+
+    /// The Anytime behind this view.
+    pub fn anytime(&self) -> &Arc<Anytime<T>> {
+        &self.anytime
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_view_pins_what_it_first_read() {
+        let anytime = Arc::new(Anytime::new());
+        let (early, late) = (anytime.view(), anytime.view());
+        anytime.update_result(1);
+        assert_eq!(early.get_result(), Some(1));
+        anytime.update_result(2);
+        assert_eq!((early.get_result(), early.pinned_version()), (Some(1), Some(1)));
+        assert!(!late.is_pinned());
+        assert_eq!(late.get_result(), Some(2));
+        assert!(!anytime.is_final());
+    }
+
+    #[test]
+    fn trying_to_read_an_empty_view_leaves_it_unpinned() {
+        let anytime = Arc::new(Anytime::new());
+        let view = anytime.view();
+        assert_eq!(view.try_get_result(), Err(NotReady));
+        assert!(!view.is_pinned() && !view.is_ready());
+        anytime.update_result(1);
+        assert_eq!(view.try_get_result(), Ok(1));
+        anytime.update_result(2);
+        assert_eq!(view.try_get_result(), Ok(1));
+    }
+}
@@ -0,0 +1,56 @@
+//! Pausing is process wide, so these tests get a process of their own.
+
+#![cfg(feature = "std")]
+
+use std::{
+    sync::{mpsc, Arc, Mutex, PoisonError},
+    thread,
+    time::Duration,
+};
+
+use anytime_rs::{is_paused, pause, resume, Anytime};
+
+/// Keeps the tests from pausing each other.
+static SERIAL: Mutex<()> = Mutex::new(());
+
+/// How long a test waits for something that should happen promptly before calling it a hang.
+const PATIENCE: Duration = Duration::from_secs(5);
+
+#[test]
+fn paused_producers_wait_until_resumed() {
+    let _serial = SERIAL.lock().unwrap_or_else(PoisonError::into_inner);
+    let anytime = Arc::new(Anytime::new());
+    let improver = anytime.improver();
+    pause();
+    assert!(is_paused() && improver.is_paused());
+    let (done, finished) = mpsc::channel();
+    let producer = thread::spawn(move || {
+        let carry_on = improver.wait_while_paused();
+        improver.update_result(1);
+        let _ = done.send(carry_on);
+    });
+    assert!(finished.recv_timeout(Duration::from_millis(50)).is_err());
+    assert!(!anytime.is_ready());
+    resume();
+    assert_eq!(finished.recv_timeout(PATIENCE), Ok(true));
+    producer.join().unwrap();
+    assert!(!is_paused());
+    assert_eq!(anytime.get_result(), Some(1));
+}
+
+#[test]
+fn finalizing_releases_paused_producers() {
+    let _serial = SERIAL.lock().unwrap_or_else(PoisonError::into_inner);
+    let anytime = Arc::new(Anytime::<u32>::new());
+    let improver = anytime.improver();
+    pause();
+    let (done, finished) = mpsc::channel();
+    let producer = thread::spawn(move || {
+        let _ = done.send(improver.wait_while_paused());
+    });
+    assert_eq!(anytime.get_result(), None);
+    let carried_on = finished.recv_timeout(PATIENCE);
+    resume();
+    assert_eq!(carried_on, Ok(false));
+    producer.join().unwrap();
+}
@@ -0,0 +1,79 @@
+//! Shutting down is process wide, so these tests get a process of their own.
+
+#![cfg(feature = "std")]
+
+use std::{
+    sync::{mpsc, Arc, Mutex, PoisonError},
+    thread,
+    time::{Duration, Instant},
+};
+
+use anytime_rs::{
+    shutdown, Anytime, AnytimeQueue, DeadlineScheduler, FinalizeReason, Improver, ProducerGroup,
+};
+
+/// Keeps the tests from shutting each other down.
+static SERIAL: Mutex<()> = Mutex::new(());
+
+/// How long a test waits for something that should happen promptly before calling it a hang.
+const PATIENCE: Duration = Duration::from_secs(5);
+
+#[test]
+fn shutting_down_finalizes_and_joins_the_background_machinery() {
+    let _serial = SERIAL.lock().unwrap_or_else(PoisonError::into_inner);
+    let mut group = ProducerGroup::new();
+    let produced = Arc::new(Anytime::new());
+    group.spawn(&produced, |improver: Improver<u32>| {
+        while improver.should_continue() {
+            improver.update_result(1);
+            thread::yield_now();
+        }
+    });
+    let queue = AnytimeQueue::new(1);
+    let queued = Arc::new(Anytime::new());
+    let far_off = Instant::now() + Duration::from_secs(600);
+    queue.push(&queued, far_off, |improver: &Improver<u32>| {
+        improver.update_result(2);
+        true
+    });
+    let scheduled = Arc::new(Anytime::<u32>::new());
+    DeadlineScheduler::global().register(&scheduled, far_off);
+    assert!(shutdown(PATIENCE));
+    for anytime in [&produced, &queued, &scheduled].iter() {
+        assert_eq!(anytime.finalize_reason(), Some(FinalizeReason::Shutdown));
+    }
+    let late = Arc::new(Anytime::<u32>::new());
+    queue.push(&late, far_off, |_: &Improver<u32>| false);
+    assert!(!late.is_final() && !late.is_ready());
+    group.finish();
+}
+
+#[test]
+fn machinery_created_after_a_shutdown_works_as_usual() {
+    let _serial = SERIAL.lock().unwrap_or_else(PoisonError::into_inner);
+    assert!(shutdown(PATIENCE));
+    let anytime = Arc::new(Anytime::<u32>::new());
+    DeadlineScheduler::global().register(&anytime, Instant::now() + Duration::from_millis(10));
+    let started = Instant::now();
+    while !anytime.is_final() {
+        assert!(started.elapsed() < PATIENCE, "the deadline never passed");
+        thread::yield_now();
+    }
+    assert_eq!(anytime.finalize_reason(), Some(FinalizeReason::Deadline));
+}
+
+#[test]
+fn shutting_down_gives_up_on_producers_that_never_stop() {
+    let _serial = SERIAL.lock().unwrap_or_else(PoisonError::into_inner);
+    let (release, released) = mpsc::channel::<()>();
+    let mut group = ProducerGroup::new();
+    let anytime = Arc::new(Anytime::<u32>::new());
+    group.spawn(&anytime, move |_| {
+        let _ = released.recv();
+    });
+    assert!(!shutdown(Duration::from_millis(20)));
+    assert_eq!(anytime.finalize_reason(), Some(FinalizeReason::Shutdown));
+    drop(release);
+    group.finish();
+    assert!(shutdown(PATIENCE));
+}